@@ -0,0 +1,218 @@
+//! Fuzzing campaigns over several targets, which share one build and take turns in
+//! time slices, given preferentially to the targets whose corpus is still growing.
+
+use crate::supervise::{self, Supervision};
+use crate::{crash_files, print_table, CorpusStats};
+use anyhow::Result;
+use std::cmp::Reverse;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// A fuzzing target of a campaign.
+#[derive(Debug, Clone)]
+pub(crate) struct Target {
+    pub(crate) binary: String,
+    /// The corpus directory, honggfuzz adds the inputs reaching new coverage to it.
+    pub(crate) corpus: PathBuf,
+    /// The directory honggfuzz saves the crashes to.
+    pub(crate) crashes: PathBuf,
+    /// The `--statsfile` of honggfuzz.
+    pub(crate) stats: PathBuf,
+    /// The directory to copy the output of honggfuzz to, if any.
+    pub(crate) logs: Option<PathBuf>,
+}
+
+/// What fuzzing a target achieved so far.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Progress {
+    pub(crate) slices: usize,
+    pub(crate) time: Duration,
+    pub(crate) corpus_before: CorpusStats,
+    pub(crate) corpus: CorpusStats,
+    pub(crate) crashes_before: CorpusStats,
+    pub(crate) crashes: CorpusStats,
+    /// Number of inputs added to the corpus during the last slice.
+    pub(crate) last_growth: usize,
+    /// The crash files of the target before fuzzing it.
+    pub(crate) known_crashes: Vec<PathBuf>,
+}
+
+impl Progress {
+    /// The state of `target` before fuzzing it.
+    pub(crate) fn start(target: &Target) -> Result<Self> {
+        let corpus = CorpusStats::collect(&target.corpus)?;
+        let crashes = CorpusStats::crashes(&target.crashes)?;
+        Ok(Self {
+            corpus_before: corpus,
+            corpus,
+            crashes_before: crashes,
+            crashes,
+            known_crashes: crash_files(&target.crashes)?,
+            ..Self::default()
+        })
+    }
+
+    /// Account for fuzzing `target` for `time`.
+    pub(crate) fn update(&mut self, target: &Target, time: Duration) -> Result<()> {
+        let corpus = CorpusStats::collect(&target.corpus)?;
+        self.slices += 1;
+        self.time += time;
+        self.last_growth = corpus.files.saturating_sub(self.corpus.files);
+        self.corpus = corpus;
+        self.crashes = CorpusStats::crashes(&target.crashes)?;
+        Ok(())
+    }
+
+    pub(crate) fn new_crashes(&self) -> usize {
+        self.crashes.files.saturating_sub(self.crashes_before.files)
+    }
+
+    /// The crash files of `target` which were found since the start.
+    pub(crate) fn new_crash_files(&self, target: &Target) -> Result<Vec<PathBuf>> {
+        let mut crashes = crash_files(&target.crashes)?;
+        crashes.retain(|crash| !self.known_crashes.contains(crash));
+        Ok(crashes)
+    }
+}
+
+/// How the time of a campaign is divided among its targets.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Schedule {
+    /// Length of a time slice.
+    pub(crate) slice: Duration,
+    /// Number of time slices, unlimited within the `budget` if `None`.
+    pub(crate) slices: Option<usize>,
+    /// Wall-clock time of the whole campaign, the last slice is shortened to fit.
+    pub(crate) budget: Option<Duration>,
+    /// Ends a slice early once the target reached no new coverage for this long.
+    pub(crate) plateau: Option<Duration>,
+}
+
+/// Fuzz `targets` according to `schedule`, `honggfuzz` gives the command fuzzing the
+/// target at the given index for the given time.
+pub(crate) fn run(
+    targets: &[Target],
+    schedule: Schedule,
+    mut honggfuzz: impl FnMut(usize, Duration) -> Result<Command>,
+) -> Result<Vec<Progress>> {
+    let mut progress = targets
+        .iter()
+        .map(Progress::start)
+        .collect::<Result<Vec<_>>>()?;
+
+    let campaign = Instant::now();
+    for number in 1.. {
+        if schedule.slices.is_some_and(|slices| number > slices) || supervise::interrupted() {
+            break;
+        }
+        let slice = match schedule.budget {
+            // honggfuzz takes whole seconds and 0 would mean no limit at all
+            Some(budget) => match budget.checked_sub(campaign.elapsed()) {
+                Some(remaining) if remaining.as_secs() > 0 => schedule.slice.min(remaining),
+                _ => break,
+            },
+            None => schedule.slice,
+        };
+        let index = next(&progress);
+        let target = &targets[index];
+        let of_slices = schedule
+            .slices
+            .map(|slices| format!("/{}", slices))
+            .unwrap_or_default();
+        println!(
+            "[{}{}] fuzzing {} for {}s",
+            number,
+            of_slices,
+            target.binary,
+            slice.as_secs()
+        );
+
+        let command = honggfuzz(index, slice)?;
+        let start = Instant::now();
+        let supervision = Supervision {
+            stats: target.stats.clone(),
+            plateau: schedule.plateau,
+            logs: target.logs.clone(),
+        };
+        let status = supervise::run(command, &supervision)?;
+        if !status.success() {
+            log::warn!(
+                "honggfuzz exited with status code {:?} while fuzzing {}",
+                status.code(),
+                target.binary
+            );
+        }
+
+        progress[index].update(target, start.elapsed())?;
+    }
+    Ok(progress)
+}
+
+/// The target to give the next slice to: every target gets a first slice, afterwards the
+/// one whose corpus grew the most during its last slice, or the least fuzzed one.
+fn next(progress: &[Progress]) -> usize {
+    progress
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, progress)| {
+            (
+                progress.slices > 0,
+                Reverse(progress.last_growth),
+                progress.time,
+            )
+        })
+        .map(|(index, _)| index)
+        .expect("a campaign has at least one target")
+}
+
+/// Print one row per target with its new crashes and the growth of its corpus.
+pub(crate) fn print(targets: &[Target], progress: &[Progress]) {
+    let mut rows = vec![[
+        "TARGET".to_owned(),
+        "SLICES".to_owned(),
+        "TIME".to_owned(),
+        "CORPUS".to_owned(),
+        "NEW CRASHES".to_owned(),
+    ]];
+    for (target, progress) in targets.iter().zip(progress) {
+        rows.push([
+            target.binary.clone(),
+            progress.slices.to_string(),
+            format!("{}s", progress.time.as_secs()),
+            format!(
+                "{} -> {} files",
+                progress.corpus_before.files, progress.corpus.files
+            ),
+            progress.new_crashes().to_string(),
+        ]);
+    }
+    print_table(rows);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(slices: usize, last_growth: usize, secs: u64) -> Progress {
+        Progress {
+            slices,
+            last_growth,
+            time: Duration::from_secs(secs),
+            ..Progress::default()
+        }
+    }
+
+    #[test]
+    fn schedule() {
+        // a target which was not fuzzed yet comes first
+        assert_eq!(next(&[progress(1, 10, 60), progress(0, 0, 0)]), 1);
+        // then the one which grew the most
+        assert_eq!(
+            next(&[progress(1, 0, 60), progress(3, 2, 180), progress(1, 5, 60)]),
+            2
+        );
+        // then the least fuzzed one
+        assert_eq!(next(&[progress(2, 0, 120), progress(1, 0, 60)]), 1);
+    }
+}
@@ -0,0 +1,238 @@
+//! Per-target fuzzing defaults, read from `[package.metadata.hongg.targets.<bin>]`
+//! of the package manifest and from `[targets.<bin>]` of a `Hongg.toml` next to it.
+//! Binaries listed in `mutators` of either are mutators rather than fuzzing targets.
+//!
+//! Values given on the command line take precedence over environment variables,
+//! which take precedence over the configuration files.
+
+use crate::metadata::FuzzTarget;
+use crate::sanitizer::Sanitizer;
+use crate::split_args;
+use anyhow::{Context, Result};
+use cargo_metadata::{Metadata, Package};
+use fs_err as fs;
+use std::collections::BTreeMap;
+use std::env;
+use std::path::PathBuf;
+
+/// Name of the standalone configuration file, an alternative to the package metadata.
+const CONFIG_FILE: &str = "Hongg.toml";
+
+/// Fuzzing defaults of a single target.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct TargetConfig {
+    /// Timeout per input in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) timeout: Option<u64>,
+    /// Number of concurrent fuzzing threads.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) threads: Option<u32>,
+    /// Path to a honggfuzz dictionary file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) dictionary: Option<PathBuf>,
+    /// Maximum size of a generated input in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) max_input_size: Option<u64>,
+    /// Additional flags given to `rustc`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) rustflags: Option<String>,
+    /// Sanitizer to build with, i.e. `address`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) sanitizer: Option<Sanitizer>,
+    /// Additional arguments given to honggfuzz.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) args: Vec<String>,
+}
+
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+struct Targets {
+    /// Binaries defined with `hongg::mutator_main!`, which are not fuzzing targets.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    mutators: Vec<String>,
+    targets: BTreeMap<String, TargetConfig>,
+}
+
+/// The configuration of `package` from its metadata and from its `Hongg.toml`, in this order.
+fn package_config(package: &Package) -> Result<[Targets; 2]> {
+    let from_metadata = match package.metadata.get("hongg") {
+        Some(hongg) => serde_json::from_value::<Targets>(hongg.clone()).with_context(|| {
+            format!(
+                "Failed to parse [package.metadata.hongg] of {}",
+                package.manifest_path
+            )
+        })?,
+        None => Targets::default(),
+    };
+    let config_file = package
+        .manifest_path
+        .with_file_name(CONFIG_FILE)
+        .into_std_path_buf();
+    let from_file = if config_file.is_file() {
+        toml::from_str::<Targets>(&fs::read_to_string(&config_file)?)
+            .with_context(|| format!("Failed to parse {}", config_file.display()))?
+    } else {
+        Targets::default()
+    };
+    Ok([from_metadata, from_file])
+}
+
+/// The binaries of `package` which are configured as mutators.
+pub(crate) fn mutators(package: &Package) -> Result<Vec<String>> {
+    Ok(Vec::from_iter(
+        package_config(package)?
+            .into_iter()
+            .flat_map(|targets| targets.mutators),
+    ))
+}
+
+impl TargetConfig {
+    /// Load the configuration of the fuzzing `target` from the package defining it.
+    ///
+    /// Both the package metadata and a `Hongg.toml` in the package directory are read,
+    /// the values of `Hongg.toml` take precedence. A relative dictionary path is relative
+    /// to the package directory.
+    pub(crate) fn load(metadata: &Metadata, target: &FuzzTarget) -> Result<Self> {
+        let binary = target.name.as_str();
+        let package = match metadata
+            .workspace_packages()
+            .into_iter()
+            .find(|package| package.name == target.package)
+        {
+            Some(package) => package,
+            None => return Ok(Self::default()),
+        };
+
+        let [from_metadata, from_file] = package_config(package)?;
+        let get = |targets: Targets| {
+            let mut targets = targets.targets;
+            targets.remove(binary).unwrap_or_default()
+        };
+        let mut config = get(from_file).or(get(from_metadata));
+        if let (Some(dictionary), Some(package_dir)) =
+            (&config.dictionary, package.manifest_path.parent())
+        {
+            config.dictionary = Some(package_dir.as_std_path().join(dictionary));
+        }
+        Ok(config)
+    }
+
+    /// Values given via environment variables.
+    pub(crate) fn from_env() -> Result<Self> {
+        let hfuzz_run_args = env::var("HFUZZ_RUN_ARGS").unwrap_or_default();
+        log::debug!("HFUZZ_RUN_ARGS: {}", hfuzz_run_args);
+
+        Ok(Self {
+            args: split_args(&hfuzz_run_args)?,
+            ..Default::default()
+        })
+    }
+
+    /// Fill all values which are not set in `self` from `defaults`.
+    ///
+    /// Additional honggfuzz arguments are concatenated, so the ones of `self`
+    /// come last and take precedence.
+    pub(crate) fn or(self, defaults: Self) -> Self {
+        Self {
+            timeout: self.timeout.or(defaults.timeout),
+            threads: self.threads.or(defaults.threads),
+            dictionary: self.dictionary.or(defaults.dictionary),
+            max_input_size: self.max_input_size.or(defaults.max_input_size),
+            rustflags: self.rustflags.or(defaults.rustflags),
+            sanitizer: self.sanitizer.or(defaults.sanitizer),
+            args: Vec::from_iter(defaults.args.into_iter().chain(self.args)),
+        }
+    }
+
+    /// Flags given to `rustc` in addition to the ones required for fuzzing.
+    pub(crate) fn extra_rustflags(&self) -> String {
+        let mut rustflags = self.rustflags.clone().unwrap_or_default();
+        if let Some(sanitizer) = self.sanitizer {
+            rustflags.push(' ');
+            rustflags.push_str(&sanitizer.rustflags());
+        }
+        rustflags
+    }
+
+    /// Arguments given to honggfuzz.
+    pub(crate) fn honggfuzz_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(timeout) = self.timeout {
+            args.push("--timeout".to_owned());
+            args.push(timeout.to_string());
+        }
+        if let Some(threads) = self.threads {
+            args.push("--threads".to_owned());
+            args.push(threads.to_string());
+        }
+        if let Some(dictionary) = &self.dictionary {
+            args.push("--dict".to_owned());
+            args.push(dictionary.display().to_string());
+        }
+        if let Some(max_input_size) = self.max_input_size {
+            args.push("--max_file_size".to_owned());
+            args.push(max_input_size.to_string());
+        }
+        args.extend(self.args.iter().cloned());
+        args
+    }
+
+    /// The configuration of `binary` in the format of `Hongg.toml`.
+    pub(crate) fn show(&self, binary: &str) -> Result<String> {
+        let targets = Targets {
+            targets: BTreeMap::from_iter([(binary.to_owned(), self.clone())]),
+            ..Default::default()
+        };
+        Ok(toml::to_string(&targets)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn precedence() {
+        let mut file = toml::from_str::<Targets>(
+            r#"
+            mutators = ["mutator"]
+
+            [targets.parse]
+            timeout = 10
+            threads = 4
+            max-input-size = 4096
+            sanitizer = "address"
+            args = ["--linux_perf_branch"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(file.mutators, vec!["mutator"]);
+        let file = file.targets.remove("parse").unwrap();
+
+        let cli = TargetConfig {
+            timeout: Some(1),
+            rustflags: Some("-C lto".to_owned()),
+            args: vec!["-v".to_owned()],
+            ..Default::default()
+        };
+
+        let resolved = cli.or(file);
+        assert_eq!(resolved.timeout, Some(1));
+        assert_eq!(resolved.threads, Some(4));
+        assert_eq!(resolved.extra_rustflags(), "-C lto -Z sanitizer=address");
+        assert_eq!(
+            resolved.honggfuzz_args(),
+            vec![
+                "--timeout",
+                "1",
+                "--threads",
+                "4",
+                "--max_file_size",
+                "4096",
+                "--linux_perf_branch",
+                "-v"
+            ]
+        );
+    }
+}
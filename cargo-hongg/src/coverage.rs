@@ -0,0 +1,138 @@
+//! Source-based coverage reports, replays the corpus through a `fuzzing_debug` build
+//! instrumented with `-C instrument-coverage` and renders the profile with `llvm-cov`.
+
+use crate::{replay, BuildDir};
+use anyhow::{Context, Result};
+use fs_err as fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Sources of the standard library and of dependencies are left out of the reports.
+const IGNORE_FILENAME_REGEX: &str = r"/rustc/|/\.cargo/registry/|/\.cargo/git/";
+
+/// Replay all `inputs` through the coverage build of `binary` in `build_dir` and write
+/// `coverage.profdata`, `lcov.info` and an HTML report to `output`.
+pub(crate) fn coverage(
+    build_dir: &BuildDir,
+    binary: &str,
+    inputs: &[PathBuf],
+    output: &Path,
+) -> Result<()> {
+    if inputs.is_empty() {
+        anyhow::bail!("The corpus is empty, there is nothing to replay");
+    }
+    let llvm_profdata = llvm_tool("llvm-profdata")?;
+    let llvm_cov = llvm_tool("llvm-cov")?;
+
+    let profraw_dir = output.join("profraw");
+    if profraw_dir.exists() {
+        fs::remove_dir_all(&profraw_dir)?;
+    }
+    fs::create_dir_all(&profraw_dir)?;
+
+    let binary = build_dir.debug_binary(binary);
+    let mut crashes = 0;
+    for input in inputs {
+        let replayed = Command::new(&binary)
+            .envs(build_dir.sanitizer_env())
+            .env("CARGO_HONGGFUZZ_CRASH_FILENAME", input)
+            .env("LLVM_PROFILE_FILE", profraw_dir.join("%p-%m.profraw"))
+            .env("RUST_BACKTRACE", "0")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()?;
+        let outcome = replay::outcome(
+            input,
+            replayed.status,
+            &String::from_utf8_lossy(&replayed.stderr),
+        )?;
+        if outcome.is_crash() {
+            log::warn!(
+                "\"{}\" crashed with {}, its coverage may be incomplete",
+                input.display(),
+                outcome
+            );
+            crashes += 1;
+        }
+    }
+    log::info!("Replayed {} inputs, {} crashed", inputs.len(), crashes);
+
+    let profdata = output.join("coverage.profdata");
+    let mut profraws = Vec::new();
+    for entry in fs::read_dir(&profraw_dir)? {
+        profraws.push(entry?.path());
+    }
+    run(Command::new(&llvm_profdata)
+        .args(["merge", "-sparse", "-o"])
+        .arg(&profdata)
+        .args(&profraws))?;
+
+    let llvm_cov_command = |args: &[&str]| {
+        let mut command = Command::new(&llvm_cov);
+        command
+            .args(args)
+            .arg(format!("-instr-profile={}", profdata.display()))
+            .arg(format!("-ignore-filename-regex={}", IGNORE_FILENAME_REGEX))
+            .arg(&binary);
+        command
+    };
+
+    let lcov = llvm_cov_command(&["export", "-format=lcov"])
+        .stderr(Stdio::inherit())
+        .output()?;
+    if !lcov.status.success() {
+        anyhow::bail!(
+            "llvm-cov export failed with status code {:?}",
+            lcov.status.code()
+        );
+    }
+    fs::write(output.join("lcov.info"), lcov.stdout)?;
+
+    run(llvm_cov_command(&["show", "-format=html"])
+        .arg(format!("-output-dir={}", output.join("html").display())))?;
+
+    run(&mut llvm_cov_command(&["report"]))?;
+
+    println!("lcov:   {}", output.join("lcov.info").display());
+    println!(
+        "html:   {}",
+        output.join("html").join("index.html").display()
+    );
+    Ok(())
+}
+
+fn run(command: &mut Command) -> Result<()> {
+    log::debug!("Spawn: {:?}", command);
+    let status = command.status()?;
+    if !status.success() {
+        anyhow::bail!("Execution failed with status code {:?}", status.code());
+    }
+    Ok(())
+}
+
+/// Locate an LLVM tool matching the LLVM version of `rustc`, as installed by the
+/// `llvm-tools` rustup component, falling back to the one in `PATH`.
+fn llvm_tool(name: &str) -> Result<PathBuf> {
+    let sysroot = Command::new("rustc")
+        .args(["--print", "sysroot"])
+        .output()
+        .context("Failed to query the sysroot of rustc")?;
+    let sysroot = PathBuf::from(String::from_utf8_lossy(&sysroot.stdout).trim());
+    let host = rustc_version::version_meta()?.host;
+    let tool = sysroot
+        .join("lib")
+        .join("rustlib")
+        .join(host)
+        .join("bin")
+        .join(name);
+    if tool.is_file() {
+        return Ok(tool);
+    }
+    which::which(name).with_context(|| {
+        format!(
+            "Failed to find `{}`, install it with `rustup component add llvm-tools`",
+            name
+        )
+    })
+}
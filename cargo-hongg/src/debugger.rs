@@ -0,0 +1,257 @@
+//! Debugger sessions on the `fuzzing_debug` build, either interactive, in batch
+//! mode which records a full backtrace of the crash, or under `gdbserver`.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Debuggers probed when none is given, with the debugger the Rust wrappers run.
+const CANDIDATES: &[(&str, &str)] = &[
+    ("rust-lldb", "lldb"),
+    ("rust-gdb", "gdb"),
+    ("gdb", "gdb"),
+    ("lldb", "lldb"),
+];
+
+/// Print the locals of every frame of the selected thread, `lldb` has no `bt full`.
+const LLDB_FRAME_VARIABLES: &str =
+    "script _ = [print(frame, frame.GetVariables(True, True, False, True), sep='\\n') for frame in lldb.thread]";
+
+/// The command line syntax of a debugger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flavor {
+    Gdb,
+    Lldb,
+}
+
+impl Flavor {
+    /// `rust-lldb`, `lldb-14`.. are `lldb`, everything else is assumed to be `gdb`.
+    fn of(debugger: &str) -> Self {
+        match Path::new(debugger).file_name() {
+            Some(name) if name.to_string_lossy().contains("lldb") => Self::Lldb,
+            _ => Self::Gdb,
+        }
+    }
+
+    /// Ask the debugger itself with `--version`, falling back on its name.
+    fn query(debugger: &str) -> Self {
+        let version = Command::new(debugger)
+            .arg("--version")
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output();
+        match version {
+            Ok(output) => {
+                let version = String::from_utf8_lossy(&output.stdout);
+                if version.contains("lldb") {
+                    Self::Lldb
+                } else if version.contains("GNU gdb") {
+                    Self::Gdb
+                } else {
+                    Self::of(debugger)
+                }
+            }
+            Err(_) => Self::of(debugger),
+        }
+    }
+}
+
+/// A debugger and the syntax of its command line.
+#[derive(Debug, Clone)]
+pub(crate) struct Debugger {
+    program: String,
+    flavor: Flavor,
+}
+
+impl Debugger {
+    /// The debugger `program`, a name in `PATH` or a path.
+    pub(crate) fn new(program: &str) -> Self {
+        Self {
+            program: program.to_owned(),
+            flavor: Flavor::query(program),
+        }
+    }
+
+    /// The first of `rust-lldb`, `rust-gdb`, `gdb` and `lldb` which is installed, the
+    /// wrappers of rustup are only picked if the debugger they run is installed too.
+    pub(crate) fn detect() -> Result<Self> {
+        CANDIDATES
+            .iter()
+            .find(|(program, debugger)| {
+                which::which(program).is_ok() && which::which(debugger).is_ok()
+            })
+            .map(|(program, _)| Self::new(program))
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No debugger found, install gdb or lldb or select one with `--debugger`"
+                )
+            })
+    }
+
+    pub(crate) fn program(&self) -> &str {
+        &self.program
+    }
+
+    /// Start the debugger on `binary`, stopping on a panic and showing a backtrace, then
+    /// leaving the prompt to the user.
+    pub(crate) fn interactive(&self, binary: &str) -> Command {
+        interactive(&self.program, self.flavor, binary)
+    }
+
+    /// Run `binary` in the debugger without any prompt, printing the backtrace with the
+    /// locals of the thread which panicked or received a signal.
+    pub(crate) fn batch(&self, binary: &str) -> Command {
+        batch(&self.program, self.flavor, binary)
+    }
+
+    /// Whether the output of a `batch` session shows that the process stopped on
+    /// the panic breakpoint or on a signal.
+    pub(crate) fn reproduced(&self, output: &str) -> bool {
+        reproduced(self.flavor, output)
+    }
+}
+
+fn interactive(debugger: &str, flavor: Flavor, binary: &str) -> Command {
+    let mut cmd = Command::new(debugger);
+    match flavor {
+        Flavor::Lldb => {
+            cmd.args([
+                "-o",
+                "b rust_panic",
+                "-o",
+                "r",
+                "-o",
+                "bt",
+                "-f",
+                binary,
+                "--",
+            ]);
+        }
+        Flavor::Gdb => {
+            cmd.args([
+                "-ex",
+                "b rust_panic",
+                "-ex",
+                "r",
+                "-ex",
+                "bt",
+                "--args",
+                binary,
+            ]);
+        }
+    };
+    cmd
+}
+
+fn batch(debugger: &str, flavor: Flavor, binary: &str) -> Command {
+    let mut cmd = Command::new(debugger);
+    match flavor {
+        Flavor::Lldb => {
+            // in batch mode lldb runs the `-k` commands instead of the remaining `-o`
+            // ones once the process stopped on a signal
+            cmd.args([
+                "--batch",
+                "-o",
+                "b rust_panic",
+                "-o",
+                "r",
+                "-o",
+                "bt",
+                "-o",
+                LLDB_FRAME_VARIABLES,
+                "-k",
+                "bt",
+                "-k",
+                LLDB_FRAME_VARIABLES,
+                "-f",
+                binary,
+                "--",
+            ]);
+        }
+        Flavor::Gdb => {
+            cmd.args([
+                "-batch",
+                "-ex",
+                "set pagination off",
+                "-ex",
+                "b rust_panic",
+                "-ex",
+                "r",
+                "-ex",
+                "bt full",
+                "--args",
+                binary,
+            ]);
+        }
+    };
+    cmd
+}
+
+fn reproduced(flavor: Flavor, output: &str) -> bool {
+    match flavor {
+        Flavor::Lldb => output.lines().any(|line| {
+            line.contains("stop reason = breakpoint") || line.contains("stop reason = signal")
+        }),
+        Flavor::Gdb => output
+            .lines()
+            .any(|line| line.contains("Breakpoint 1,") || line.contains("received signal SIG")),
+    }
+}
+
+/// Start `binary` under `gdbserver` listening on `address`, like `localhost:1234`,
+/// for a debugger to attach remotely.
+pub(crate) fn gdbserver(address: &str, binary: &str) -> Result<Command> {
+    let gdbserver = which::which("gdbserver")
+        .context("Failed to find `gdbserver`, it is usually packaged along with gdb")?;
+    let mut cmd = Command::new(gdbserver);
+    cmd.arg(address).arg(binary);
+    Ok(cmd)
+}
+
+/// The backtrace of a crash is saved next to it, `SIGABRT.[..].fuzz.backtrace` for `SIGABRT.[..].fuzz`.
+pub(crate) fn backtrace_file(crash_file: &Path) -> PathBuf {
+    let mut path = crash_file.as_os_str().to_owned();
+    path.push(".backtrace");
+    PathBuf::from(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flavor() {
+        assert_eq!(Flavor::of("rust-lldb"), Flavor::Lldb);
+        assert_eq!(Flavor::of("/usr/bin/lldb-14"), Flavor::Lldb);
+        assert_eq!(Flavor::of("rust-gdb"), Flavor::Gdb);
+        assert_eq!(Flavor::of("/opt/lldb/bin/gdb"), Flavor::Gdb);
+    }
+
+    #[test]
+    fn reproduction() {
+        assert!(reproduced(
+            Flavor::Gdb,
+            "Thread 1 \"parse\" hit Breakpoint 1, std::panicking::rust_panic (...)"
+        ));
+        assert!(reproduced(
+            Flavor::Gdb,
+            "Program received signal SIGSEGV, Segmentation fault."
+        ));
+        assert!(!reproduced(
+            Flavor::Gdb,
+            "Breakpoint 1 at 0x4f0a4: file src/panicking.rs, line 862.\n[Inferior 1 (process 42) exited with code 02]\nNo stack."
+        ));
+        assert!(reproduced(
+            Flavor::Lldb,
+            "* thread #1, name = 'parse', stop reason = breakpoint 1.1"
+        ));
+        assert!(reproduced(
+            Flavor::Lldb,
+            "* thread #1, name = 'parse', stop reason = signal SIGABRT"
+        ));
+        assert!(!reproduced(
+            Flavor::Lldb,
+            "Process 42 exited with status = 2 (0x00000002)"
+        ));
+    }
+}
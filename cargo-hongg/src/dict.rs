@@ -0,0 +1,214 @@
+//! Dictionary extraction, collects the tokens embedded with `hongg::dict!` and optionally
+//! the printable strings of the fuzzing target into a honggfuzz dictionary.
+
+use anyhow::{Context, Result};
+use fs_err as fs;
+use object::{Object, ObjectSection, SectionKind};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Name of the linker section written by `hongg::dict!`.
+const SECTION: &str = "hongg_dict";
+
+/// Printable strings shorter than this are too common to be useful.
+const MIN_STRING_LEN: usize = 4;
+
+/// Longer printable strings are usually messages or several literals laid out back to back.
+const MAX_STRING_LEN: usize = 64;
+
+/// Strings of the standard library, the runtime and dependencies rather than of the fuzzed code.
+const IGNORED_STRINGS: &[&str] = &[
+    "/rustc/",
+    ".cargo/",
+    ".rs",
+    ".py",
+    "/lib",
+    "panicked",
+    "called `",
+    "fatal runtime error",
+    "failed to",
+    "assertion",
+    "out of bounds",
+    "out of range",
+    "RUST_",
+    ".debug_",
+    ".gnu",
+];
+
+/// Share of ASCII alphanumeric characters, or of punctuation common in tokens, a printable
+/// string needs, to skip the runs of punctuation found in lookup tables.
+const MIN_TOKEN_RATIO: f64 = 0.8;
+
+/// Punctuation common in tokens like `HTTP/1.1`, `keep-alive` or `text/x-www-form-urlencoded`.
+const TOKEN_PUNCTUATION: &[u8] = b"-_./:";
+
+/// Strings with more spaces are messages, and pieces of a formatted message if they start
+/// or end with a space.
+const MAX_SPACES: usize = 2;
+
+/// Honggfuzz only loads a limited number of entries, the embedded tokens come first.
+const MAX_TOKENS: usize = 1024;
+
+/// The dictionary of `binary` in the workspace, passed to honggfuzz when it exists.
+pub(crate) fn path(workspace: &str, binary: &str) -> PathBuf {
+    Path::new(workspace)
+        .join(binary)
+        .join(format!("{}.dict", binary))
+}
+
+/// Extract the tokens of the fuzzing target at `binary` and write them to `output`,
+/// with `strings` also the printable strings of its read-only data.
+pub(crate) fn dict(binary: &str, output: &Path, strings: bool) -> Result<()> {
+    let data = fs::read(binary)?;
+    let file = object::File::parse(data.as_slice())
+        .with_context(|| format!("Failed to parse the executable {}", binary))?;
+
+    let mut embedded = Vec::new();
+    let mut printable = Vec::new();
+    for section in file.sections() {
+        let name = section.name().unwrap_or_default();
+        if name == SECTION {
+            embedded.extend(decode(section.data()?));
+        } else if strings
+            && matches!(
+                section.kind(),
+                SectionKind::ReadOnlyData | SectionKind::ReadOnlyString
+            )
+        {
+            printable.extend(printable_strings(section.data()?));
+        }
+    }
+
+    let mut seen = BTreeSet::new();
+    let mut tokens = Vec::new();
+    let mut from_strings = 0;
+    let candidates = embedded
+        .iter()
+        .map(|token| (token, false))
+        .chain(printable.iter().map(|token| (token, true)));
+    for (token, is_string) in candidates {
+        if tokens.len() == MAX_TOKENS {
+            break;
+        }
+        if seen.insert(*token) {
+            tokens.push(*token);
+            from_strings += usize::from(is_string);
+        }
+    }
+
+    let mut dictionary = format!(
+        "# dictionary of `{}`, generated by `cargo hongg dict`\n",
+        binary
+    );
+    for token in &tokens {
+        writeln!(dictionary, "\"{}\"", escape(token))?;
+    }
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(output, dictionary)?;
+    println!(
+        "{} embedded tokens and {} strings written to {}",
+        tokens.len() - from_strings,
+        from_strings,
+        output.display()
+    );
+    Ok(())
+}
+
+/// Split the content of the `hongg_dict` section into tokens, the statics of `dict!`
+/// are byte arrays so the linker lays them out back to back.
+fn decode(mut records: &[u8]) -> Vec<&[u8]> {
+    let mut tokens = Vec::new();
+    while let [low, high, rest @ ..] = records {
+        let len = u16::from_le_bytes([*low, *high]) as usize;
+        if len > rest.len() {
+            log::warn!("Ignoring the truncated end of the {} section", SECTION);
+            break;
+        }
+        if len > 0 {
+            tokens.push(&rest[..len]);
+        }
+        records = &rest[len..];
+    }
+    tokens
+}
+
+/// Runs of printable ASCII characters in `data`, like `strings` does, which look like
+/// literals of the fuzzed code.
+fn printable_strings(data: &[u8]) -> Vec<&[u8]> {
+    data.split(|byte| !(byte.is_ascii_graphic() || *byte == b' '))
+        .map(strip_length)
+        .filter(|run| (MIN_STRING_LEN..=MAX_STRING_LEN).contains(&run.len()))
+        .filter(|run| {
+            let token = run
+                .iter()
+                .filter(|byte| byte.is_ascii_alphanumeric() || TOKEN_PUNCTUATION.contains(byte))
+                .count();
+            token as f64 >= run.len() as f64 * MIN_TOKEN_RATIO
+        })
+        .filter(|run| {
+            let spaces = run.iter().filter(|byte| **byte == b' ').count();
+            spaces <= MAX_SPACES && !run.starts_with(b" ") && !run.ends_with(b" ")
+        })
+        // random bytes of tables rarely contain a word, like three letters of the same case
+        .filter(|run| {
+            run.windows(3).any(|word| {
+                word.iter().all(u8::is_ascii_lowercase) || word.iter().all(u8::is_ascii_uppercase)
+            })
+        })
+        .filter(|run| {
+            let run = String::from_utf8_lossy(run);
+            !IGNORED_STRINGS.iter().any(|ignored| run.contains(ignored))
+        })
+        .collect()
+}
+
+/// Drop the length byte some strings are prefixed with, when it happens to be printable.
+fn strip_length(run: &[u8]) -> &[u8] {
+    match run {
+        [len, rest @ ..] if *len as usize == rest.len() => rest,
+        run => run,
+    }
+}
+
+/// Quote `token` for a honggfuzz dictionary, which uses the escapes of AFL.
+fn escape(token: &[u8]) -> String {
+    let mut escaped = String::with_capacity(token.len());
+    for &byte in token {
+        match byte {
+            b'"' | b'\\' => {
+                escaped.push('\\');
+                escaped.push(byte as char);
+            }
+            b' '..=b'~' => escaped.push(byte as char),
+            _ => escaped.push_str(&format!("\\x{:02x}", byte)),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens() {
+        assert_eq!(
+            decode(b"\x04\x00GET \x02\x00\r\n\x00\x00\x02\x00\xff\xfe\x09\x00trunc"),
+            vec![&b"GET "[..], b"\r\n", b"\xff\xfe"]
+        );
+        assert_eq!(
+            printable_strings(b"\x00HTTP/1.1\x01ab\x02/rustc/abc/lib.rs\x00Content-Length"),
+            vec![&b"HTTP/1.1"[..], b"Content-Length"]
+        );
+        assert_eq!(
+            printable_strings(
+                b"\x00!application/x-www-form-urlencoded\x01!\"#$%&'()*+\x02/lib64/ld-linux-x86-64.so.2"
+            ),
+            vec![&b"application/x-www-form-urlencoded"[..]]
+        );
+        assert_eq!(escape(b"a \"b\"\\\r\n\xff"), r#"a \"b\"\\\x0d\x0a\xff"#);
+    }
+}
@@ -0,0 +1,126 @@
+//! Export of crashes as regression tests, which replay each crash through
+//! the harness of the fuzzing target with `hongg::replay`.
+
+use crate::tmin::CrashSignature;
+use anyhow::Result;
+use fs_err as fs;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Write a test module to `output` with one test per crash, calling the function at the Rust
+/// path `harness` with the crash. The crashes are copied to a directory next to `output`,
+/// named like it without extension, i.e. `tests/fuzz_regressions/` for `tests/fuzz_regressions.rs`.
+pub(crate) fn export_tests(
+    binary: &str,
+    harness: &str,
+    crashes: &[PathBuf],
+    output: &Path,
+) -> Result<()> {
+    let data_dir = output.with_extension("");
+    let data_dir_name = data_dir
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("\"{}\" is not a file", output.display()))?
+        .to_string_lossy()
+        .into_owned();
+    fs::create_dir_all(&data_dir)?;
+
+    let mut module = format!(
+        "//! Regression tests for crashes found by fuzzing `{}`, generated by `cargo hongg export-tests`.\n\
+         //!\n\
+         //! The crashing inputs are stored in `{}/`.\n",
+        binary, data_dir_name
+    );
+    let mut names = BTreeSet::new();
+    for crash in crashes {
+        let file_name = crash
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("\"{}\" is not a file", crash.display()))?
+            .to_string_lossy()
+            .into_owned();
+
+        let mut name = test_name(&file_name);
+        let mut suffix = 1;
+        while names.contains(&name) {
+            suffix += 1;
+            name = format!("{}_{}", test_name(&file_name), suffix);
+        }
+        names.insert(name.clone());
+        // crashes of different directories may have the same file name, the test names are unique
+        let data_file = format!("{}.fuzz", name);
+        fs::copy(crash, data_dir.join(&data_file))?;
+
+        write!(
+            module,
+            "\n/// {}\n#[test]\nfn {}() {{\n    hongg::replay::run(include_bytes!({:?}), {});\n}}\n",
+            crash.display(),
+            name,
+            format!("{}/{}", data_dir_name, data_file),
+            harness
+        )?;
+    }
+
+    fs::write(output, module)?;
+    println!("exported {} crashes to {}", crashes.len(), output.display());
+    Ok(())
+}
+
+/// Name of the test for the crash file `file_name`, based on the signal and stack hash
+/// of crashes saved by honggfuzz, otherwise on the file name itself.
+fn test_name(file_name: &str) -> String {
+    let name = match CrashSignature::from_file_name(file_name) {
+        Some(signature) => format!("{}_{}", signature.signal, signature.stack),
+        None => file_name
+            .strip_suffix(".fuzz")
+            .unwrap_or(file_name)
+            .to_owned(),
+    };
+    let words = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase);
+    Vec::from_iter(std::iter::once("crash".to_owned()).chain(words)).join("_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        assert_eq!(
+            test_name("SIGABRT.PC.7ffff7a42428.STACK.18a2f29c6e.CODE.-6.ADDR.0.INSTR.mov____0x108(%rsp),%rax.fuzz"),
+            "crash_sigabrt_18a2f29c6e"
+        );
+        assert_eq!(test_name("my input.bin"), "crash_my_input_bin");
+        assert_eq!(test_name("-x-.fuzz"), "crash_x");
+    }
+
+    #[test]
+    fn same_file_names() {
+        let dir = std::env::temp_dir().join(format!("cargo-hongg-export-{}", std::process::id()));
+        let crashes = vec![
+            dir.join("a").join("crash.fuzz"),
+            dir.join("b").join("crash.fuzz"),
+        ];
+        for (crash, content) in crashes.iter().zip(["a", "b"]) {
+            fs::create_dir_all(crash.parent().unwrap()).unwrap();
+            fs::write(crash, content).unwrap();
+        }
+        let output = dir.join("tests").join("fuzz_regressions.rs");
+        export_tests("parse", "fz::harness", &crashes, &output).unwrap();
+
+        let data_dir = dir.join("tests").join("fuzz_regressions");
+        assert_eq!(
+            fs::read_to_string(data_dir.join("crash_crash.fuzz")).unwrap(),
+            "a"
+        );
+        assert_eq!(
+            fs::read_to_string(data_dir.join("crash_crash_2.fuzz")).unwrap(),
+            "b"
+        );
+        let module = fs::read_to_string(&output).unwrap();
+        assert!(module.contains(r#"include_bytes!("fuzz_regressions/crash_crash_2.fuzz")"#));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
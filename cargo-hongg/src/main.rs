@@ -1,10 +1,31 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
 use std::os::unix::process::CommandExt;
 use std::path::{Path, PathBuf};
 use std::process::{self, Command};
-use std::time::Duration;
+use std::slice;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use config::TargetConfig;
+use sanitizer::Sanitizer;
+
+mod campaign;
+mod config;
+mod coverage;
+mod debugger;
+mod dict;
+mod export;
+mod metadata;
+mod minimize;
+mod replay;
+mod report;
+mod sanitizer;
+mod stats;
+mod supervise;
+mod tmin;
+mod triage;
 
 /// The version of `cargo-hongg` cli tooling.
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -37,27 +58,158 @@ struct CommonOpts {
     #[clap(long)]
     only_build: bool,
 
-    /// flags given to `rustc`, for example "-Z sanitizer=address"
+    /// flags given to `rustc`, for example "-C lto"
     #[clap(long, env = "RUSTFLAGS")]
     rustflags: Option<String>,
 
+    /// build with a sanitizer, requires a nightly toolchain
+    #[clap(long, value_enum)]
+    sanitizer: Option<Sanitizer>,
+
+    /// build for the target triple, defaults to the host
+    #[clap(long, value_name = "TRIPLE")]
+    target: Option<String>,
+
     /// args given to `cargo build`
     #[clap(long, env = "HFUZZ_BUILD_ARGS")]
     build_args: Option<String>,
 
-    /// path to working directory for storing all relevant fuzz data
+    /// path to working directory for storing all relevant fuzz data, relative to the package
     #[clap(
-        short,
+        short = 'w',
         long,
         default_value = "hfuzz_workspace",
         env = "HFUZZ_WORKSPACE"
     )]
-    workspace: String,
+    hfuzz_workspace: String,
+
+    #[clap(flatten)]
+    manifest: clap_cargo::Manifest,
+
+    #[clap(flatten)]
+    packages: clap_cargo::Workspace,
+
+    #[clap(flatten)]
+    features: clap_cargo::Features,
 
     #[clap(flatten)]
     verbosity: clap_verbosity_flag::Verbosity,
 }
 
+impl CommonOpts {
+    /// Args given to `cargo build` to select the package at `crate_root` and its features.
+    fn cargo_args(&self, crate_root: &Path) -> Vec<String> {
+        let mut args = vec![
+            "--manifest-path".to_owned(),
+            crate_root.join("Cargo.toml").display().to_string(),
+        ];
+        if self.features.all_features {
+            args.push("--all-features".to_owned());
+        }
+        if self.features.no_default_features {
+            args.push("--no-default-features".to_owned());
+        }
+        if !self.features.features.is_empty() {
+            args.push("--features".to_owned());
+            args.push(self.features.features.join(","));
+        }
+        args
+    }
+
+    /// Fuzzing data directory of the package at `crate_root`.
+    fn workspace(&self, crate_root: &Path) -> String {
+        crate_root.join(&self.hfuzz_workspace).display().to_string()
+    }
+
+    /// Args given to `cargo build`, split like a shell would do.
+    fn build_args(&self) -> Result<Vec<String>> {
+        let build_args = self.build_args.as_deref().unwrap_or_default();
+        log::debug!("HFUZZ_BUILD_ARGS: {}", build_args);
+        split_args(build_args)
+    }
+
+    /// Locations of the build artifacts, builds with a sanitizer get a separate
+    /// directory so they don't invalidate the ones without.
+    fn build_dir(&self, target_dir: &str, sanitizer: Option<Sanitizer>) -> Result<BuildDir> {
+        let target_triple = match &self.target {
+            Some(target) => target.clone(),
+            None => target_triple()?,
+        };
+        let path = match sanitizer {
+            Some(sanitizer) => format!("{}/{}", target_dir, sanitizer.name()),
+            None => target_dir.to_owned(),
+        };
+        Ok(BuildDir {
+            path,
+            target_triple,
+            profile: hfuzz_build_profile(&self.build_args()?),
+            sanitizer,
+        })
+    }
+
+    /// Resolve the configuration of `binary`, values given on the command line
+    /// or via environment take precedence over the ones of the configuration `file`.
+    fn resolve(&self, binary: &str, cli: TargetConfig, file: TargetConfig) -> Result<TargetConfig> {
+        let cli = TargetConfig {
+            rustflags: self.rustflags.clone(),
+            sanitizer: self.sanitizer,
+            ..cli
+        };
+        let config = cli.or(file);
+        if log::log_enabled!(log::Level::Info) {
+            log::info!(
+                "Resolved configuration:\n{}",
+                config.show(binary)?.trim_end()
+            );
+        }
+        Ok(config)
+    }
+
+    /// Build `binary` of the package at `crate_root` into `build_dir`.
+    fn build(
+        &self,
+        crate_root: &Path,
+        binary: &str,
+        config: &TargetConfig,
+        build_type: BuildType,
+        build_dir: &BuildDir,
+    ) -> Result<()> {
+        hfuzz_build(
+            &[binary],
+            &config.extra_rustflags(),
+            &self.build_args()?,
+            self.cargo_args(crate_root),
+            crate_root,
+            build_type,
+            build_dir,
+        )
+    }
+}
+
+/// Resolve the configuration of `binary` and build it, `None` if only building was asked for.
+fn build_target(
+    common: &CommonOpts,
+    crate_root: &Path,
+    target_dir: &str,
+    binary: &str,
+    file_config: TargetConfig,
+    build_type: BuildType,
+) -> Result<Option<BuildDir>> {
+    let config = common.resolve(binary, TargetConfig::default(), file_config)?;
+    let mut build_dir = common.build_dir(target_dir, config.sanitizer)?;
+    if build_type == BuildType::Coverage {
+        // separate from the debug build, which is not instrumented
+        build_dir.path = format!("{}/coverage", build_dir.path);
+    }
+    common.build(crate_root, binary, &config, build_type, &build_dir)?;
+    if common.only_build {
+        return Ok(None);
+    }
+    Ok(Some(build_dir))
+}
+
+// parsed once, the size of the variants does not matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, clap::Subcommand)]
 enum SubCommand {
     /// build and run fuzzing
@@ -70,16 +222,69 @@ enum SubCommand {
         input: Option<String>,
 
         /// which fuzzing target binary to fuzz
-        #[clap(short = 'b', long = "bin")]
-        binary: String,
+        #[clap(
+            short = 'b',
+            long = "bin",
+            required_unless_present_any = ["all_targets", "bins"],
+            conflicts_with = "all_targets"
+        )]
+        binary: Option<String>,
+
+        /// fuzz these targets after one build, taking turns in time slices
+        #[clap(
+            long,
+            value_delimiter = ',',
+            conflicts_with_all = ["all_targets", "binary", "mutator", "output", "crashdir", "covdir_new"]
+        )]
+        bins: Vec<String>,
+
+        /// fuzz all targets of the selected packages after one build, taking turns in time slices
+        #[clap(
+            long,
+            conflicts_with_all = ["binary", "mutator", "output", "crashdir", "covdir_new"]
+        )]
+        all_targets: bool,
+
+        /// length of a time slice when fuzzing several targets, like `600` seconds or `10m`
+        #[clap(long, value_name = "DURATION", default_value = "600")]
+        slice: TimeoutDuration,
+
+        /// number of time slices when fuzzing several targets
+        /// [default: one per target, or as many as fit in `--max-total-time`]
+        #[clap(long)]
+        slices: Option<usize>,
+
+        /// stop once no input reached new coverage for this long, like `20m`,
+        /// as reported by honggfuzz in `$HFUZZ_WORKSPACE/{TARGET}/stats.csv`
+        #[clap(long, value_name = "DURATION")]
+        stop_after_plateau: Option<TimeoutDuration>,
+
+        /// run honggfuzz as a child process, forwarding SIGINT and SIGTERM to it,
+        /// and copy its output to `$HFUZZ_WORKSPACE/{TARGET}/logs/`
+        #[clap(long)]
+        supervise: bool,
+
+        /// actions to run once honggfuzz exited, implies `--supervise`
+        #[clap(long, value_enum, value_delimiter = ',', value_name = "ACTIONS")]
+        then: Vec<PostAction>,
+
+        /// write a JSON summary of the run to this file, implies `--supervise`
+        #[clap(long, value_name = "PATH")]
+        report_json: Option<PathBuf>,
+
+        /// write a JUnit XML summary of the run to this file, with a test case per target
+        /// which fails on new crashes, implies `--supervise`
+        #[clap(long, value_name = "PATH")]
+        report_junit: Option<PathBuf>,
 
         /// do no build with compiler instrumentation
         #[clap(long)]
         no_instr: bool,
 
-        /// use grcov coverage information
-        #[clap(long)]
-        grcov: bool,
+        /// binary of the package built with `hongg::mutator_main!`, which generates the inputs
+        /// instead of the internal mutators of honggfuzz
+        #[clap(long, value_name = "BIN", conflicts_with = "all_targets")]
+        mutator: Option<String>,
 
         #[clap(flatten)]
         launch: HonggfuzzLaunchArgs,
@@ -95,8 +300,9 @@ enum SubCommand {
         common: CommonOpts,
 
         /// name or path to debugger, like `rust-gdb`, `gdb`, `/usr/bin/lldb-7`..
-        #[clap(short, long, default_value = "rust-lldb", env = "HFUZZ_DEBUGGER")]
-        debugger: String,
+        /// [default: the first installed of rust-lldb, rust-gdb, gdb and lldb]
+        #[clap(short, long, env = "HFUZZ_DEBUGGER")]
+        debugger: Option<String>,
 
         /// which binary target to fuzz
         #[clap(short, long)]
@@ -106,58 +312,638 @@ enum SubCommand {
         #[clap(short, long)]
         crash_file: PathBuf,
 
+        /// run without a prompt and save the backtrace with locals next to the crash file,
+        /// exits with 0 if the crash reproduced and 1 otherwise
+        #[clap(long)]
+        batch: bool,
+
+        /// start the target under `gdbserver` listening on this address, like `localhost:1234`,
+        /// to attach a debugger remotely
+        #[clap(long, value_name = "ADDR", conflicts_with = "batch")]
+        gdbserver: Option<String>,
+
         /// args to target
         target_args: Vec<String>,
     },
 
-    /// Minimize
-    Minimize,
+    /// Replay the corpus through a build instrumented for source-based coverage
+    /// and report the coverage as lcov, HTML and summary
+    Coverage {
+        #[clap(flatten)]
+        common: CommonOpts,
+
+        /// which fuzzing target binary to measure the coverage of
+        #[clap(short = 'b', long = "bin")]
+        binary: String,
+
+        /// path to fuzzer's input files (aka "corpus"), defaults to `$HFUZZ_WORKSPACE/{TARGET}/input`
+        #[clap(short, long, env = "HFUZZ_INPUT")]
+        input: Option<String>,
+
+        /// directory to write the reports to, defaults to `$HFUZZ_WORKSPACE/{TARGET}/coverage`
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Replay inputs through the debug build, reporting the ones which crash
+    Replay {
+        #[clap(flatten)]
+        common: CommonOpts,
+
+        /// which fuzzing target binary to replay the inputs with
+        #[clap(short = 'b', long = "bin")]
+        binary: String,
+
+        /// input files or directories containing them, like `hfuzz_workspace/[TARGET]`
+        #[clap(required = true)]
+        inputs: Vec<PathBuf>,
+
+        /// write a JSON summary of the replay to this file
+        #[clap(long, value_name = "PATH")]
+        report_json: Option<PathBuf>,
+
+        /// write a JUnit XML summary of the replay to this file, which fails if an input crashed
+        #[clap(long, value_name = "PATH")]
+        report_junit: Option<PathBuf>,
+    },
+
+    /// Extract the tokens embedded with `hongg::dict!` into a dictionary, optionally along with
+    /// the printable strings of a fuzzing target, which `fuzz` passes to honggfuzz
+    Dict {
+        #[clap(flatten)]
+        common: CommonOpts,
+
+        /// which fuzzing target binary to extract the dictionary from
+        #[clap(short = 'b', long = "bin")]
+        binary: String,
+
+        /// dictionary file to write [default: $HFUZZ_WORKSPACE/{TARGET}/{TARGET}.dict]
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        /// also add the printable strings of the binary, which may include some of std and dependencies
+        #[clap(long)]
+        strings: bool,
+    },
+
+    /// Replay crashes through the debug build and group them by their backtrace
+    Triage {
+        #[clap(flatten)]
+        common: CommonOpts,
+
+        /// which fuzzing target binary produced the crashes
+        #[clap(short = 'b', long = "bin")]
+        binary: String,
+
+        /// number of frames of the fuzzed code, which crashes must share to be grouped
+        #[clap(long, default_value_t = triage::DEFAULT_FRAMES)]
+        frames: usize,
+
+        /// crash files or directories containing them, defaults to the `.fuzz` files in `$HFUZZ_WORKSPACE/{TARGET}`
+        crashes: Vec<PathBuf>,
+    },
+
+    /// Turn crashes into regression tests, replaying them through a harness function
+    ExportTests {
+        #[clap(flatten)]
+        common: CommonOpts,
+
+        /// which fuzzing target binary produced the crashes
+        #[clap(short = 'b', long = "bin")]
+        binary: String,
+
+        /// path of the function taking a `&[u8]` which contains the body of the `fuzz!` block,
+        /// like `my_crate::parse_harness`
+        #[clap(long)]
+        harness: String,
+
+        /// test module to write, relative to the package
+        #[clap(short, long, default_value = "tests/fuzz_regressions.rs")]
+        output: PathBuf,
+
+        /// only export the representative of each group of crashes, as reported by `triage`
+        #[clap(long)]
+        triage: bool,
+
+        /// crash files or directories containing them, defaults to the `.fuzz` files in `$HFUZZ_WORKSPACE/{TARGET}`
+        crashes: Vec<PathBuf>,
+    },
+
+    /// Minimize the corpus, keeping only inputs which contribute to coverage
+    #[clap(group(
+        clap::ArgGroup::new("destination")
+            .required(true)
+            .args(["output", "in_place"])
+    ))]
+    Minimize {
+        #[clap(flatten)]
+        common: CommonOpts,
+
+        /// which fuzzing target binary to minimize the corpus for
+        #[clap(short = 'b', long = "bin")]
+        binary: String,
+
+        /// path to fuzzer's input files (aka "corpus"), relative to `$HFUZZ_WORKSPACE/{TARGET}`
+        #[clap(short, long, env = "HFUZZ_INPUT")]
+        input: Option<String>,
+
+        /// directory to write the minimized corpus to
+        #[clap(short, long)]
+        output: Option<String>,
+
+        /// minimize the corpus in place, removing redundant files from the input directory
+        #[clap(long)]
+        in_place: bool,
+    },
+    /// Reduce a single crashing input while preserving the crash
+    Tmin {
+        #[clap(flatten)]
+        common: CommonOpts,
+
+        /// which fuzzing target binary produced the crash
+        #[clap(short = 'b', long = "bin")]
+        binary: String,
+
+        /// path to crash file, typically like `hfuzz_workspace/[TARGET]/[..].fuzz`
+        #[clap(short, long)]
+        crash_file: PathBuf,
+
+        /// replay through the debug build and preserve the panic location,
+        /// instead of the signal and stack hash reported by honggfuzz
+        #[clap(long)]
+        debug: bool,
+    },
+
+    /// Show the statistics honggfuzz writes while fuzzing a target
+    Stats {
+        #[clap(flatten)]
+        common: CommonOpts,
+
+        /// which fuzzing target binary to show the statistics of
+        #[clap(short = 'b', long = "bin")]
+        binary: String,
+
+        /// print the statistics as JSON, one object per line when following
+        #[clap(long)]
+        json: bool,
+
+        /// keep printing the statistics of a running campaign as they are written
+        #[clap(short, long)]
+        follow: bool,
+    },
+
+    /// List all fuzzing targets of the workspace, with the size of their corpus and crashes
+    List {
+        /// path to working directory for storing all relevant fuzz data, relative to the package
+        #[clap(
+            short = 'w',
+            long,
+            default_value = "hfuzz_workspace",
+            env = "HFUZZ_WORKSPACE"
+        )]
+        hfuzz_workspace: String,
+
+        #[clap(flatten)]
+        manifest: clap_cargo::Manifest,
+
+        #[clap(flatten)]
+        packages: clap_cargo::Workspace,
+
+        #[clap(flatten)]
+        verbosity: clap_verbosity_flag::Verbosity,
+    },
+
     /// Clean the saved fuzzing state and all related files.
-    Clean { args: Vec<String> },
+    Clean {
+        #[clap(flatten)]
+        manifest: clap_cargo::Manifest,
+
+        #[clap(flatten)]
+        packages: clap_cargo::Workspace,
+
+        /// args given to `cargo clean`
+        args: Vec<String>,
+    },
 }
 
 impl SubCommand {
     pub fn verbosity(&self) -> log::LevelFilter {
         match self {
-            Self::Clean { .. } | Self::Minimize => log::LevelFilter::Trace,
-            Self::Debug { common, .. } | Self::Fuzz { common, .. } => {
-                common.verbosity.log_level_filter()
+            Self::Clean { .. } => log::LevelFilter::Trace,
+            Self::List { verbosity, .. } => verbosity.log_level_filter(),
+            Self::Debug { common, .. }
+            | Self::Fuzz { common, .. }
+            | Self::Replay { common, .. }
+            | Self::Dict { common, .. }
+            | Self::Stats { common, .. }
+            | Self::Coverage { common, .. }
+            | Self::Triage { common, .. }
+            | Self::ExportTests { common, .. }
+            | Self::Minimize { common, .. }
+            | Self::Tmin { common, .. } => common.verbosity.log_level_filter(),
+        }
+    }
+
+    /// The fuzzing target binary the sub-command operates on.
+    fn binary(&self) -> Option<&str> {
+        match self {
+            Self::Clean { .. } | Self::List { .. } => None,
+            Self::Fuzz { binary, .. } => binary.as_deref(),
+            Self::Debug { binary, .. }
+            | Self::Replay { binary, .. }
+            | Self::Dict { binary, .. }
+            | Self::Stats { binary, .. }
+            | Self::Coverage { binary, .. }
+            | Self::Triage { binary, .. }
+            | Self::ExportTests { binary, .. }
+            | Self::Minimize { binary, .. }
+            | Self::Tmin { binary, .. } => Some(binary),
+        }
+    }
+
+    /// The fuzzing target binaries the sub-command operates on, `None` for all of them.
+    fn binaries(&self) -> Option<Vec<&str>> {
+        match self {
+            Self::Fuzz { all_targets, .. } if *all_targets => None,
+            Self::Fuzz { bins, .. } if !bins.is_empty() => {
+                Some(Vec::from_iter(bins.iter().map(String::as_str)))
             }
+            _ => Some(Vec::from_iter(self.binary())),
+        }
+    }
+
+    /// Whether the package is selected with `--manifest-path`, `--package` or `--workspace`
+    /// rather than by the current directory.
+    fn selects_package(&self) -> bool {
+        let (manifest, packages) = match self {
+            Self::List {
+                manifest, packages, ..
+            }
+            | Self::Clean {
+                manifest, packages, ..
+            } => (manifest, packages),
+            Self::Debug { common, .. }
+            | Self::Fuzz { common, .. }
+            | Self::Replay { common, .. }
+            | Self::Dict { common, .. }
+            | Self::Stats { common, .. }
+            | Self::Coverage { common, .. }
+            | Self::Triage { common, .. }
+            | Self::ExportTests { common, .. }
+            | Self::Minimize { common, .. }
+            | Self::Tmin { common, .. } => (&common.manifest, &common.packages),
+        };
+        manifest.manifest_path.is_some() || *packages != clap_cargo::Workspace::default()
+    }
+
+    fn common(&self) -> Option<&CommonOpts> {
+        match self {
+            Self::Clean { .. } | Self::List { .. } => None,
+            Self::Debug { common, .. }
+            | Self::Fuzz { common, .. }
+            | Self::Replay { common, .. }
+            | Self::Dict { common, .. }
+            | Self::Stats { common, .. }
+            | Self::Coverage { common, .. }
+            | Self::Triage { common, .. }
+            | Self::ExportTests { common, .. }
+            | Self::Minimize { common, .. }
+            | Self::Tmin { common, .. } => Some(common),
         }
     }
 }
 
 impl SubCommand {
     pub fn launch(self, crate_root: impl AsRef<Path>) -> Result<()> {
-        let crate_root = crate_root.as_ref();
-        let target_triple = target_triple()?;
         let target_dir = env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| HONGGFUZZ_TARGET.into());
 
+        // the package defining the fuzzing targets is the crate root, so the layout of
+        // the build and fuzzing data does not depend on where we are invoked from
+        let (crate_root, file_configs) = match (self.binaries(), self.common()) {
+            (Some(binaries), _) if binaries.is_empty() => {
+                (crate_root.as_ref().to_path_buf(), BTreeMap::new())
+            }
+            (binaries, Some(common)) => {
+                let metadata = metadata::load(&common.manifest)?;
+                let targets = metadata::fuzz_targets(&metadata, &common.packages)?;
+                let selected = match binaries {
+                    Some(binaries) => binaries
+                        .into_iter()
+                        .map(|binary| metadata::validate_binary(&targets, binary))
+                        .collect::<Result<Vec<_>>>()?,
+                    None => Vec::from_iter(&targets),
+                };
+                let Some(first) = selected.first() else {
+                    anyhow::bail!("No fuzzing targets found in the selected packages");
+                };
+                if selected
+                    .iter()
+                    .any(|target| target.package != first.package)
+                {
+                    anyhow::bail!(
+                        "The selected targets belong to several packages, select one with `-p`"
+                    );
+                }
+                let mut file_configs = BTreeMap::new();
+                for target in &selected {
+                    file_configs
+                        .insert(target.name.clone(), TargetConfig::load(&metadata, target)?);
+                }
+                (first.crate_root().to_path_buf(), file_configs)
+            }
+            _ => (crate_root.as_ref().to_path_buf(), BTreeMap::new()),
+        };
+        let file_config = self
+            .binary()
+            .and_then(|binary| file_configs.get(binary).cloned())
+            .unwrap_or_default();
+        let crate_root = crate_root.as_path();
+        let target_dir = crate_root.join(target_dir).display().to_string();
+
         match self {
-            Self::Clean { args } => {
-                hfuzz_clean(args, &target_dir)?;
+            Self::Clean {
+                manifest,
+                packages,
+                args,
+            } => {
+                hfuzz_clean(args, &manifest, &packages)?;
+            }
+            Self::Stats {
+                common,
+                binary,
+                json,
+                follow,
+            } => {
+                let workspace = common.workspace(crate_root);
+                stats::stats(&binary, &stats::path(&workspace, &binary), json, follow)?;
+            }
+            Self::List {
+                hfuzz_workspace,
+                manifest,
+                packages,
+                ..
+            } => {
+                metadata::list(&metadata::load(&manifest)?, &packages, &hfuzz_workspace)?;
+            }
+            Self::Minimize {
+                common,
+                binary,
+                input,
+                output,
+                in_place,
+            } => {
+                let Some(build_dir) = build_target(
+                    &common,
+                    crate_root,
+                    &target_dir,
+                    &binary,
+                    file_config,
+                    BuildType::ReleaseInstrumented,
+                )?
+                else {
+                    return Ok(());
+                };
+                let workspace = common.workspace(crate_root);
+                let input = input.unwrap_or_else(|| format!("{}/{}/input", workspace, binary));
+                // honggfuzz removes the redundant files of the input without an output
+                let output = if in_place {
+                    None
+                } else {
+                    Some(output.expect("the destination is either --output or --in-place"))
+                };
+
+                minimize::minimize(&build_dir, &binary, &input, output.as_deref(), &workspace)?;
+            }
+            Self::Coverage {
+                common,
+                binary,
+                input,
+                output,
+            } => {
+                let Some(build_dir) = build_target(
+                    &common,
+                    crate_root,
+                    &target_dir,
+                    &binary,
+                    file_config,
+                    BuildType::Coverage,
+                )?
+                else {
+                    return Ok(());
+                };
+
+                let workspace = common.workspace(crate_root);
+                let input = input.unwrap_or_else(|| format!("{}/{}/input", workspace, binary));
+                let output =
+                    output.unwrap_or_else(|| Path::new(&workspace).join(&binary).join("coverage"));
+                coverage::coverage(
+                    &build_dir,
+                    &binary,
+                    &replay::collect_inputs(&[PathBuf::from(input)])?,
+                    &output,
+                )?;
+            }
+            Self::Replay {
+                common,
+                binary,
+                inputs,
+                report_json,
+                report_junit,
+            } => {
+                let Some(build_dir) = build_target(
+                    &common,
+                    crate_root,
+                    &target_dir,
+                    &binary,
+                    file_config,
+                    BuildType::Debug,
+                )?
+                else {
+                    return Ok(());
+                };
+
+                let inputs = replay::collect_inputs(&inputs)?;
+                let start = Instant::now();
+                let outcomes = replay::replay_all(&build_dir, &binary, &inputs)?;
+                let crashes = outcomes
+                    .iter()
+                    .filter(|(_, outcome)| outcome.is_crash())
+                    .count();
+                println!("replayed {} inputs, {} crashed", outcomes.len(), crashes);
+                if report_json.is_some() || report_junit.is_some() {
+                    let mut target = report::TargetReport::new(&binary, start.elapsed());
+                    target.properties.insert("inputs", outcomes.len() as u64);
+                    target.properties.insert("crashes", crashes as u64);
+                    target.crashes = Vec::from_iter(
+                        outcomes
+                            .iter()
+                            .filter(|(_, outcome)| outcome.is_crash())
+                            .map(|(path, outcome)| report::Crash {
+                                path: path.clone(),
+                                outcome: outcome.to_string(),
+                            }),
+                    );
+                    let report = report::Report {
+                        suite: "replay".to_owned(),
+                        targets: vec![target],
+                    };
+                    report.write(report_json.as_deref(), report_junit.as_deref())?;
+                }
+                if crashes > 0 {
+                    process::exit(1);
+                }
+            }
+            Self::Dict {
+                common,
+                binary,
+                output,
+                strings,
+            } => {
+                let Some(build_dir) = build_target(
+                    &common,
+                    crate_root,
+                    &target_dir,
+                    &binary,
+                    file_config,
+                    BuildType::Debug,
+                )?
+                else {
+                    return Ok(());
+                };
+
+                let output =
+                    output.unwrap_or_else(|| dict::path(&common.workspace(crate_root), &binary));
+                dict::dict(&build_dir.debug_binary(&binary), &output, strings)?;
+            }
+            Self::Triage {
+                common,
+                binary,
+                frames,
+                crashes,
+            } => {
+                let Some(build_dir) = build_target(
+                    &common,
+                    crate_root,
+                    &target_dir,
+                    &binary,
+                    file_config,
+                    BuildType::Debug,
+                )?
+                else {
+                    return Ok(());
+                };
+
+                let crashes = if crashes.is_empty() {
+                    crash_files(Path::new(&common.workspace(crate_root)).join(&binary))?
+                } else {
+                    replay::collect_inputs(&crashes)?
+                };
+                let triage = triage::triage(&build_dir, &binary, &crashes, frames)?;
+                triage::print(&triage);
+            }
+            Self::ExportTests {
+                common,
+                binary,
+                harness,
+                output,
+                triage,
+                crashes,
+            } => {
+                let crashes = if crashes.is_empty() {
+                    crash_files(Path::new(&common.workspace(crate_root)).join(&binary))?
+                } else {
+                    replay::collect_inputs(&crashes)?
+                };
+                let crashes = if triage {
+                    let Some(build_dir) = build_target(
+                        &common,
+                        crate_root,
+                        &target_dir,
+                        &binary,
+                        file_config,
+                        BuildType::Debug,
+                    )?
+                    else {
+                        return Ok(());
+                    };
+                    let triage =
+                        triage::triage(&build_dir, &binary, &crashes, triage::DEFAULT_FRAMES)?;
+                    Vec::from_iter(
+                        triage
+                            .buckets
+                            .iter()
+                            .map(|bucket| bucket.representative().clone()),
+                    )
+                } else {
+                    crashes
+                };
+                if crashes.is_empty() {
+                    anyhow::bail!("No crashes to export");
+                }
+
+                export::export_tests(&binary, &harness, &crashes, &crate_root.join(output))?;
             }
-            Self::Minimize => {
-                // https://github.com/rust-fuzz/honggfuzz-rs/issues/26
-                todo!(" --minimize --input .. --output ..")
+            Self::Tmin {
+                common,
+                binary,
+                crash_file,
+                debug,
+            } => {
+                let build_type = if debug {
+                    BuildType::Debug
+                } else {
+                    BuildType::ReleaseInstrumented
+                };
+                let Some(build_dir) = build_target(
+                    &common,
+                    crate_root,
+                    &target_dir,
+                    &binary,
+                    file_config,
+                    build_type,
+                )?
+                else {
+                    return Ok(());
+                };
+
+                let scratch = Path::new(&common.workspace(crate_root))
+                    .join(&binary)
+                    .join(".tmin");
+                let minimized = if debug {
+                    tmin::tmin_debug(&build_dir, &binary, &crash_file, &scratch)
+                } else {
+                    tmin::tmin_release(&build_dir, &binary, &crash_file, &scratch)
+                };
+                let _ = fs::remove_dir_all(&scratch);
+                let minimized = minimized?;
+
+                println!(
+                    "minimized {} bytes to {} bytes: {}",
+                    fs::metadata(&crash_file)?.len(),
+                    fs::metadata(&minimized)?.len(),
+                    minimized.display()
+                );
             }
             Self::Fuzz {
                 launch,
                 common,
                 no_instr,
+                mutator,
                 binary,
+                bins: _,
+                all_targets: _,
+                slice,
+                slices,
+                stop_after_plateau,
+                supervise,
+                mut then,
+                report_json,
+                report_junit,
                 input,
-                grcov,
                 args,
             } => {
                 let build_type = if no_instr {
                     BuildType::ReleaseNotInstrumented
-                } else if grcov {
-                    // grcov and instrumentation are mutually exclusive,
-                    // only due to the fact, grcov is used in debug mode
-                    // where instrumentation is commonly used in release
-                    // mode.
-                    BuildType::ProfileWithGrcov
                 } else {
                     BuildType::ReleaseInstrumented
                 };
@@ -169,75 +955,287 @@ impl SubCommand {
                     .take_while(|arg| arg != "--")
                     .collect::<Vec<_>>();
                 let target_args = args.collect::<Vec<_>>();
-                let rustflags = common
-                    .rustflags
-                    .as_ref()
-                    .map(|x| x.as_ref())
-                    .unwrap_or_default();
-                let workspace = common.workspace;
+                let cli = TargetConfig {
+                    args: target_args,
+                    ..launch.target_config()
+                };
+                let workspace = common.workspace(crate_root);
+                let mut configs = Vec::new();
+                for (binary, file_config) in file_configs {
+                    let mut config = common.resolve(
+                        &binary,
+                        cli.clone().or(TargetConfig::from_env()?),
+                        file_config,
+                    )?;
+                    let dictionary = dict::path(&workspace, &binary);
+                    if config.dictionary.is_none() && dictionary.is_file() {
+                        log::info!("Using the dictionary {}", dictionary.display());
+                        config.dictionary = Some(dictionary);
+                    }
+                    configs.push((binary, config));
+                }
+                // all targets are built by a single cargo invocation
+                let (_, config) = &configs[0];
+                if configs.iter().any(|(_, other)| {
+                    other.extra_rustflags() != config.extra_rustflags()
+                        || other.sanitizer != config.sanitizer
+                }) {
+                    anyhow::bail!(
+                        "The selected targets are configured with different rustflags or sanitizers, fuzz them separately"
+                    );
+                }
+                let build_dir = common.build_dir(&target_dir, config.sanitizer)?;
                 hfuzz_build(
-                    &binary,
-                    rustflags,
-                    build_args,
-                    &crate_root,
+                    &Vec::from_iter(configs.iter().map(|(binary, _)| binary.as_str())),
+                    &config.extra_rustflags(),
+                    &common.build_args()?,
+                    common.cargo_args(crate_root).into_iter().chain(build_args),
+                    crate_root,
                     build_type,
-                    &target_dir,
+                    &build_dir,
                 )?;
+                // the mutator is a plain release build, it gets its own build directory so
+                // the builds don't invalidate each other
+                let mutator = match mutator {
+                    Some(mutator) => {
+                        let build_dir = BuildDir {
+                            path: format!("{}/mutator", build_dir.path),
+                            sanitizer: None,
+                            ..build_dir.clone()
+                        };
+                        hfuzz_build(
+                            &[mutator.as_str()],
+                            config.rustflags.as_deref().unwrap_or_default(),
+                            &common.build_args()?,
+                            common.cargo_args(crate_root),
+                            crate_root,
+                            BuildType::Mutator,
+                            &build_dir,
+                        )?;
+                        Some(build_dir.instrumented_binary(&mutator))
+                    }
+                    None => None,
+                };
                 if common.only_build {
                     return Ok(());
                 }
-                let input =
-                    input.unwrap_or_else(|| format!("{}/{}/input", workspace, binary.to_string()));
 
-                hfuzz_run(
-                    launch,
-                    &target_triple,
-                    &binary,
-                    target_args,
-                    &input,
-                    &crate_root,
-                    build_type,
-                    &workspace,
-                    &target_dir,
-                )?;
+                let plateau = stop_after_plateau.map(|plateau| plateau.0);
+                let reports = report_json.is_some() || report_junit.is_some();
+                let supervised = supervise || !then.is_empty() || reports;
+                let target = |binary: &str, corpus: PathBuf| campaign::Target {
+                    binary: binary.to_owned(),
+                    corpus,
+                    crashes: launch
+                        .crashdir
+                        .clone()
+                        .unwrap_or_else(|| PathBuf::from(format!("{}/{}", workspace, binary))),
+                    stats: stats::path(&workspace, binary),
+                    logs: supervised.then(|| supervise::logs(&workspace, binary)),
+                };
+                if let Some(binary) = binary {
+                    let input = input.unwrap_or_else(|| format!("{}/{}/input", workspace, binary));
+                    if !supervised && plateau.is_none() {
+                        return hfuzz_run(
+                            launch,
+                            config,
+                            &build_dir,
+                            &binary,
+                            &input,
+                            &workspace,
+                            mutator.as_deref(),
+                        );
+                    }
+
+                    // honggfuzz has to be watched, so it cannot replace the current process
+                    let honggfuzz = honggfuzz_command(
+                        &launch,
+                        config,
+                        &build_dir,
+                        &binary,
+                        &input,
+                        &workspace,
+                        mutator.as_deref(),
+                    )?;
+                    let target = target(
+                        &binary,
+                        launch.output.clone().unwrap_or_else(|| input.into()),
+                    );
+                    let mut progress = campaign::Progress::start(&target)?;
+                    let supervision = supervise::Supervision {
+                        stats: target.stats.clone(),
+                        plateau,
+                        logs: target.logs.clone(),
+                    };
+                    let started = unix_time();
+                    let start = Instant::now();
+                    let status = supervise::run(honggfuzz, &supervision)?;
+                    progress.update(&target, start.elapsed())?;
+                    if reports {
+                        let report = report::Report {
+                            suite: "fuzz".to_owned(),
+                            targets: vec![fuzz_report(
+                                &common, crate_root, config, &build_dir, &target, &progress,
+                                started,
+                            )?],
+                        };
+                        report.write(report_json.as_deref(), report_junit.as_deref())?;
+                    }
+                    post_actions(
+                        &then, &common, crate_root, config, &build_dir, &target, &progress,
+                    )?;
+                    if !status.success() {
+                        process::exit(status.code().unwrap_or(1));
+                    }
+                    return Ok(());
+                }
+
+                if let Some(input) = input {
+                    // an `--input` on the command line was rejected while parsing
+                    log::warn!(
+                        "Ignoring HFUZZ_INPUT={}, every target is fuzzed with its own corpus",
+                        input
+                    );
+                }
+                let targets = Vec::from_iter(configs.iter().map(|(binary, _)| {
+                    target(
+                        binary,
+                        PathBuf::from(format!("{}/{}/input", workspace, binary)),
+                    )
+                }));
+                let budget = launch.max_total_time.as_ref().map(|budget| budget.0);
+                let schedule = campaign::Schedule {
+                    slice: slice.0,
+                    slices: slices.or_else(|| budget.is_none().then_some(targets.len())),
+                    budget,
+                    plateau,
+                };
+                let started = unix_time();
+                let progress = campaign::run(&targets, schedule, |index, time| {
+                    let (binary, config) = &configs[index];
+                    let launch = HonggfuzzLaunchArgs {
+                        run_time: Some(time.as_secs()),
+                        ..launch.clone()
+                    };
+                    honggfuzz_command(
+                        &launch,
+                        config,
+                        &build_dir,
+                        binary,
+                        &targets[index].corpus.display().to_string(),
+                        &workspace,
+                        None,
+                    )
+                })?;
+                campaign::print(&targets, &progress);
+                if reports {
+                    let mut report = report::Report {
+                        suite: "fuzz".to_owned(),
+                        targets: Vec::new(),
+                    };
+                    for ((target, progress), (_, config)) in
+                        targets.iter().zip(&progress).zip(&configs)
+                    {
+                        report.targets.push(fuzz_report(
+                            &common, crate_root, config, &build_dir, target, progress, started,
+                        )?);
+                    }
+                    report.write(report_json.as_deref(), report_junit.as_deref())?;
+                }
+                // the table above already is the summary of the campaign
+                then.retain(|action| *action != PostAction::Summary);
+                for ((target, progress), (_, config)) in targets.iter().zip(&progress).zip(&configs)
+                {
+                    if supervise::interrupted() {
+                        println!("interrupted, skipping the post-actions");
+                        break;
+                    }
+                    if progress.slices > 0 {
+                        post_actions(
+                            &then, &common, crate_root, config, &build_dir, target, progress,
+                        )?;
+                    }
+                }
             }
             Self::Debug {
                 common,
                 binary,
                 target_args,
                 crash_file,
+                batch,
+                gdbserver,
                 debugger,
             } => {
-                let build_type = BuildType::Debug;
-
-                let rustflags = common
-                    .rustflags
-                    .as_ref()
-                    .map(|x| x.as_ref())
-                    .unwrap_or_default();
-
-                hfuzz_build(
-                    &binary,
-                    rustflags,
-                    common.build_args,
-                    &crate_root,
-                    build_type,
+                let Some(build_dir) = build_target(
+                    &common,
+                    crate_root,
                     &target_dir,
-                )?;
-
-                if common.only_build {
+                    &binary,
+                    file_config,
+                    BuildType::Debug,
+                )?
+                else {
+                    return Ok(());
+                };
+                let binary_path = build_dir.debug_binary(&binary);
+                let (mut command, debugger) = match gdbserver {
+                    Some(address) => {
+                        if let Some(debugger) = &debugger {
+                            log::debug!("The debugger {} is unused with --gdbserver", debugger);
+                        }
+                        let command = debugger::gdbserver(&address, &binary_path)?;
+                        println!(
+                            "attach with: gdb -ex 'target remote {}' {}",
+                            address, binary_path
+                        );
+                        (command, None)
+                    }
+                    None => {
+                        let debugger = match debugger {
+                            Some(program) => debugger::Debugger::new(&program),
+                            None => debugger::Debugger::detect()?,
+                        };
+                        let command = if batch {
+                            debugger.batch(&binary_path)
+                        } else {
+                            debugger.interactive(&binary_path)
+                        };
+                        (command, Some(debugger))
+                    }
+                };
+                command
+                    .args(target_args)
+                    .envs(build_dir.sanitizer_env())
+                    .env("CARGO_HONGGFUZZ_CRASH_FILENAME", &crash_file)
+                    .env(
+                        "RUST_BACKTRACE",
+                        env::var("RUST_BACKTRACE").unwrap_or_else(|_| "1".into()),
+                    );
+                log::debug!("Spawn: {:?}", command);
+
+                if let (true, Some(debugger)) = (batch, &debugger) {
+                    let output = command
+                        .stdin(process::Stdio::null())
+                        .output()
+                        .with_context(|| format!("Failed to run `{}`", debugger.program()))?;
+                    let log = String::from_utf8_lossy(&output.stdout).into_owned()
+                        + &String::from_utf8_lossy(&output.stderr);
+                    let backtrace_file = debugger::backtrace_file(&crash_file);
+                    fs::write(&backtrace_file, &log)?;
+                    if !debugger.reproduced(&log) {
+                        println!("not reproduced, see {}", backtrace_file.display());
+                        process::exit(1);
+                    }
+                    println!(
+                        "reproduced, backtrace saved to {}",
+                        backtrace_file.display()
+                    );
                     return Ok(());
                 }
-                let status =
-                    debugger_command(&binary.to_string(), &target_dir, &target_triple, &debugger)
-                        .args(target_args)
-                        .env("CARGO_HONGGFUZZ_CRASH_FILENAME", crash_file)
-                        .env(
-                            "RUST_BACKTRACE",
-                            env::var("RUST_BACKTRACE").unwrap_or_else(|_| "1".into()),
-                        )
-                        .status()?;
 
+                let status = command
+                    .status()
+                    .with_context(|| format!("Failed to run {:?}", command.get_program()))?;
                 if !status.success() {
                     process::exit(status.code().unwrap_or(1));
                 }
@@ -247,43 +1245,371 @@ impl SubCommand {
     }
 }
 
+/// A duration given in whole seconds, like `90`, or with units, like `2h30m`.
 #[derive(Debug, Clone)]
 struct TimeoutDuration(Duration);
 
 impl std::str::FromStr for TimeoutDuration {
-    type Err = <u64 as std::str::FromStr>::Err;
+    type Err = humantime::DurationError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self(Duration::from_secs(u64::from_str(s)?)))
+        match u64::from_str(s) {
+            Ok(secs) => Ok(Self(Duration::from_secs(secs))),
+            Err(_) => humantime::parse_duration(s).map(Self),
+        }
     }
 }
 
 impl TimeoutDuration {
+    /// Whole seconds as taken by honggfuzz, rounded up so a sub-second duration is not 0.
     fn as_secs(&self) -> u64 {
-        self.0.as_secs() as _
+        self.0.as_secs() + u64::from(self.0.subsec_nanos() > 0)
     }
 }
 
+/// Options of honggfuzz itself, see
+/// [USAGE](https://github.com/google/honggfuzz/blob/master/docs/USAGE.md) for details.
 #[derive(Debug, Clone, Default, clap::Parser)]
 struct HonggfuzzLaunchArgs {
+    /// timeout per input in seconds
     #[clap(long)]
     timeout: Option<TimeoutDuration>,
 
+    /// wall-clock limit of the whole run, like `3600` seconds or `2h30m`
+    #[clap(long, value_name = "DURATION")]
+    max_total_time: Option<TimeoutDuration>,
+
+    /// exit upon the first crash with the given exit code
     #[clap(long)]
     exit_upon_crash: Option<u32>,
 
+    /// number of fuzzing iterations, unlimited by default
     #[clap(long)]
     n_iterations: Option<u64>,
 
+    /// suppress the output of the fuzzed binary
     #[clap(long)]
     quietly: bool,
+
+    /// number of concurrent fuzzing threads
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..))]
+    threads: Option<u32>,
+
+    /// dictionary file with tokens used during mutation
+    #[clap(long, value_parser = existing_file)]
+    dict: Option<PathBuf>,
+
+    /// maximum size of an input in bytes
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+    max_file_size: Option<u64>,
+
+    /// maximum number of mutations applied per input
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..))]
+    mutations_per_run: Option<u32>,
+
+    /// address space limit of the fuzzed binary in MiB
+    #[clap(long)]
+    rlimit_as: Option<u64>,
+
+    /// resident set size limit of the fuzzed binary in MiB
+    #[clap(long)]
+    rlimit_rss: Option<u64>,
+
+    /// directory to store new coverage corpus files in, instead of the input directory
+    #[clap(long, value_parser = absolute_path)]
+    output: Option<PathBuf>,
+
+    /// directory to store crashes in, instead of the workspace
+    #[clap(long, value_parser = absolute_path)]
+    crashdir: Option<PathBuf>,
+
+    /// directory to store only the newly found coverage corpus files in
+    #[clap(long, value_parser = absolute_path)]
+    covdir_new: Option<PathBuf>,
+
+    /// save all crashing inputs, regardless of their stack hash being a duplicate
+    #[clap(long)]
+    save_all: bool,
+
+    /// file with stack hashes to ignore, one per line
+    #[clap(long, value_parser = existing_file)]
+    stackhash_bl: Option<PathBuf>,
+
+    /// number of seconds after which honggfuzz stops, set per slice of a campaign
+    #[clap(skip)]
+    run_time: Option<u64>,
+}
+
+impl HonggfuzzLaunchArgs {
+    /// Options which can also be given via the configuration file.
+    fn target_config(&self) -> TargetConfig {
+        TargetConfig {
+            timeout: self.timeout.as_ref().map(TimeoutDuration::as_secs),
+            threads: self.threads,
+            dictionary: self.dict.clone(),
+            max_input_size: self.max_file_size,
+            ..Default::default()
+        }
+    }
+
+    /// Arguments given to honggfuzz for the options which are not part of the target configuration.
+    fn honggfuzz_args(&self) -> Vec<String> {
+        let mut arguments = Vec::new();
+        let mut push = |flag: &str, value: Option<String>| {
+            if let Some(value) = value {
+                arguments.push(flag.to_owned());
+                arguments.push(value);
+            }
+        };
+        push("-N", self.n_iterations.map(|n| n.to_string()));
+        push(
+            "--run_time",
+            self.run_time
+                .or(self.max_total_time.as_ref().map(TimeoutDuration::as_secs))
+                .map(|n| n.to_string()),
+        );
+        push(
+            "--mutations_per_run",
+            self.mutations_per_run.map(|n| n.to_string()),
+        );
+        push("--rlimit_as", self.rlimit_as.map(|n| n.to_string()));
+        push("--rlimit_rss", self.rlimit_rss.map(|n| n.to_string()));
+        push("--output", self.output.as_deref().map(path_arg));
+        push("--crashdir", self.crashdir.as_deref().map(path_arg));
+        push("--covdir_new", self.covdir_new.as_deref().map(path_arg));
+        push("--stackhash_bl", self.stackhash_bl.as_deref().map(path_arg));
+        if self.quietly {
+            arguments.push("--quietly".to_owned());
+        }
+        if self.save_all {
+            arguments.push("--save_all".to_owned());
+        }
+        // if launch.verbose > 0 {
+        //     arguments.push("--verbose".to_owned());
+        // }
+        if let Some(exitcode) = self.exit_upon_crash {
+            arguments.push("--exit_upon_crash".to_owned());
+            arguments.push("--exit_code_upon_crash".to_owned());
+            arguments.push(exitcode.to_string());
+        }
+        arguments
+    }
+}
+
+fn path_arg(path: &Path) -> String {
+    path.display().to_string()
+}
+
+/// Parse a path argument, which must point to an existing file.
+fn existing_file(s: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(s);
+    if !path.is_file() {
+        return Err(format!("file \"{}\" does not exist", s));
+    }
+    // the working directory changes to the crate root before the file is used
+    fs::canonicalize(&path).map_err(|e| format!("file \"{}\": {}", s, e))
+}
+
+/// Parse a path argument relative to the current directory, which may not exist yet.
+fn absolute_path(s: &str) -> Result<PathBuf, String> {
+    // the working directory changes to the crate root before the path is used
+    std::path::absolute(s).map_err(|e| format!("path \"{}\": {}", s, e))
+}
+
+/// The value given to `--workspace`, which used to set the honggfuzz workspace and now
+/// selects all packages of the cargo workspace like it does for `cargo build`.
+fn renamed_workspace_arg(args: &[String]) -> Option<&str> {
+    let end = args
+        .iter()
+        .position(|arg| arg == "--")
+        .unwrap_or(args.len());
+    let args = &args[..end];
+    args.iter().enumerate().find_map(|(index, arg)| {
+        if let Some(value) = arg.strip_prefix("--workspace=") {
+            return Some(value);
+        }
+        let next = args.get(index + 1)?;
+        (arg == "--workspace" && !next.starts_with('-')).then_some(next.as_str())
+    })
+}
+
+fn renamed_workspace_note(workspace: &str) -> String {
+    format!(
+        "`--workspace` selects all packages of the cargo workspace, the honggfuzz workspace is set \
+         with `-w`/`--hfuzz-workspace`, like `--hfuzz-workspace {}`",
+        workspace
+    )
+}
+
+/// Parse the command line, rejecting an `--input` given with several targets to fuzz.
+///
+/// Clap can't tell the value of `--input` from the one of `HFUZZ_INPUT`, which may be set
+/// for fuzzing single targets, so the conflict is checked here and only for the former.
+fn parse_args(args: &[String]) -> Result<Opt, clap::Error> {
+    let mut command = <Opt as clap::CommandFactory>::command();
+    let matches = command.try_get_matches_from_mut(args)?;
+    if let Some(("fuzz", fuzz)) = matches.subcommand() {
+        let several = fuzz.get_flag("all_targets")
+            || fuzz.value_source("bins") == Some(clap::parser::ValueSource::CommandLine);
+        if several && fuzz.value_source("input") == Some(clap::parser::ValueSource::CommandLine) {
+            let fuzz = command
+                .find_subcommand_mut("fuzz")
+                .expect("the fuzz sub-command was matched");
+            return Err(fuzz.error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "the argument '--input' cannot be used with '--bins' or '--all-targets', \
+                 every target is fuzzed with the corpus in `$HFUZZ_WORKSPACE/{TARGET}/input`",
+            ));
+        }
+    }
+    <Opt as clap::FromArgMatches>::from_arg_matches(&matches)
+}
+
+/// Split arguments given as a single string, respecting quotes and escapes like a shell would do.
+fn split_args(args: &str) -> Result<Vec<String>> {
+    shell_words::split(args)
+        .map_err(|e| anyhow::anyhow!("Failed to split arguments \"{}\": {}", args, e))
+}
+
+/// Location of the build artifacts.
+#[derive(Debug, Clone)]
+struct BuildDir {
+    /// Target directory, separate from the one of regular builds.
+    path: String,
+    /// Target triple the binaries are built for.
+    target_triple: String,
+    /// Cargo profile of instrumented builds.
+    profile: String,
+    /// Sanitizer the binaries are built with.
+    sanitizer: Option<Sanitizer>,
+}
+
+impl BuildDir {
+    /// Path of the instrumented binary as produced by `hfuzz_build`.
+    fn instrumented_binary(&self, binary: &str) -> String {
+        format!(
+            "{}/{}/{}/{}",
+            self.path, self.target_triple, self.profile, binary
+        )
+    }
+
+    /// Path of the `fuzzing_debug` binary as produced by `hfuzz_build`.
+    fn debug_binary(&self, binary: &str) -> String {
+        format!("{}/{}/debug/{}", self.path, self.target_triple, binary)
+    }
+
+    /// Path of the honggfuzz executable placed by `hongg`'s build script.
+    fn honggfuzz(&self) -> String {
+        format!("{}/honggfuzz", self.path)
+    }
+
+    /// Arguments given to honggfuzz for the binaries built here.
+    fn honggfuzz_args(&self) -> Vec<String> {
+        if self.sanitizer.is_some() {
+            vec!["--sanitizers".to_owned()]
+        } else {
+            vec![]
+        }
+    }
+
+    /// Environment for running the binaries built here.
+    fn sanitizer_env(&self) -> Vec<(&'static str, String)> {
+        sanitizer::runtime_env(self.sanitizer)
+    }
+}
+
+/// Number of files and their accumulated size of a corpus directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct CorpusStats {
+    files: usize,
+    bytes: u64,
+}
+
+impl CorpusStats {
+    /// Collect the stats of all regular files directly within `dir`,
+    /// a non-existent directory is considered empty.
+    fn collect(dir: impl AsRef<Path>) -> Result<Self> {
+        Self::collect_matching(dir, |_| true)
+    }
+
+    /// Collect the stats of all crash files, as saved by honggfuzz
+    /// with a `.fuzz` extension, directly within `dir`.
+    fn crashes(dir: impl AsRef<Path>) -> Result<Self> {
+        Self::collect_matching(dir, is_crash_file)
+    }
+
+    fn collect_matching(dir: impl AsRef<Path>, filter: impl Fn(&Path) -> bool) -> Result<Self> {
+        let dir = dir.as_ref();
+        let mut stats = Self::default();
+        if !dir.is_dir() {
+            return Ok(stats);
+        }
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_file() && filter(&entry.path()) {
+                stats.files += 1;
+                stats.bytes += metadata.len();
+            }
+        }
+        Ok(stats)
+    }
+}
+
+/// All crash files, as saved by honggfuzz with a `.fuzz` extension, directly within `dir`.
+fn crash_files(dir: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    let mut crashes = Vec::new();
+    if !dir.is_dir() {
+        return Ok(crashes);
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && is_crash_file(&path) {
+            crashes.push(path);
+        }
+    }
+    crashes.sort();
+    Ok(crashes)
+}
+
+/// Print `rows` as columns aligned to the left, the first row being the header.
+fn print_table<const N: usize>(rows: Vec<[String; N]>) {
+    let mut widths = [0; N];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = std::cmp::max(*width, cell.len());
+        }
+    }
+    for row in rows {
+        let line = Vec::from_iter(
+            row.iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{:width$}", cell, width = width)),
+        )
+        .join("  ");
+        println!("{}", line.trim_end());
+    }
+}
+
+fn is_crash_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "fuzz")
+}
+
+impl std::fmt::Display for CorpusStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} files, {} bytes", self.files, self.bytes)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum BuildType {
     ReleaseInstrumented,
     ReleaseNotInstrumented,
-    ProfileWithGrcov,
+    Coverage,
     Debug,
+    /// A plain release build, without `--cfg fuzzing` and without building honggfuzz.
+    Mutator,
 }
 
 #[inline(always)]
@@ -292,9 +1618,9 @@ fn target_triple() -> Result<String> {
 }
 
 fn cargo_bin() -> Result<String> {
-    Ok(env::var("CARGO").or_else::<anyhow::Error, _>(|_e| {
+    env::var("CARGO").or_else::<anyhow::Error, _>(|_e| {
         Ok(which::which("cargo").map(|p: PathBuf| ToString::to_string(&p.display()))?)
-    })?)
+    })
 }
 
 fn find_crate_root() -> Result<PathBuf> {
@@ -311,164 +1637,249 @@ fn find_crate_root() -> Result<PathBuf> {
     Ok(path.to_path_buf())
 }
 
-fn debugger_command(
-    binary: &str,
-    target_dir: &str,
-    target_triple: &str,
-    debugger: &str,
-) -> Command {
-    let mut cmd = Command::new(&debugger);
-
-    let dest = format!("{}/{}/debug/{}", &target_dir, target_triple, binary);
-    match Path::new(&debugger)
-        .file_name()
-        .map(|f| f.to_string_lossy().contains("lldb"))
-    {
-        Some(true) => {
-            cmd.args(&[
-                "-o",
-                "b rust_panic",
-                "-o",
-                "r",
-                "-o",
-                "bt",
-                "-f",
-                &dest,
-                "--",
-            ]);
+/// The cargo profile used for instrumented builds, `release` unless
+/// overridden with `--profile=<label>` in the build args.
+fn hfuzz_build_profile(build_args: &[String]) -> String {
+    if let Some(arg) = build_args.iter().find(|f| f.starts_with("--profile=")) {
+        arg.split('=')
+            .nth(1)
+            .expect("--profile not in correct format (eg. --profile=<label>)")
+            .to_owned()
+    } else {
+        "release".to_owned()
+    }
+}
+
+/// Actions run once honggfuzz exited in supervised mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum PostAction {
+    /// group the new crashes by their backtrace
+    Triage,
+    /// print the new crashes and the growth of the corpus
+    Summary,
+    /// remove the inputs which add no coverage from the corpus
+    Minimize,
+}
+
+/// Run the post-`actions` for `target`, which was fuzzed with the build in `build_dir`.
+fn post_actions(
+    actions: &[PostAction],
+    common: &CommonOpts,
+    crate_root: &Path,
+    config: &TargetConfig,
+    build_dir: &BuildDir,
+    target: &campaign::Target,
+    progress: &campaign::Progress,
+) -> Result<()> {
+    for action in actions {
+        // the replays of an interrupted run would end with the signal too
+        if supervise::interrupted() {
+            println!(
+                "interrupted, skipping the post-actions of {}",
+                target.binary
+            );
+            break;
         }
-        _ => {
-            cmd.args(&[
-                "-ex",
-                "b rust_panic",
-                "-ex",
-                "r",
-                "-ex",
-                "bt",
-                "--args",
-                &dest,
-            ]);
+        match action {
+            PostAction::Triage => {
+                let crashes = progress.new_crash_files(target)?;
+                if crashes.is_empty() {
+                    println!("no new crashes of {} to triage", target.binary);
+                    continue;
+                }
+                common.build(
+                    crate_root,
+                    &target.binary,
+                    config,
+                    BuildType::Debug,
+                    build_dir,
+                )?;
+                let triage =
+                    triage::triage(build_dir, &target.binary, &crashes, triage::DEFAULT_FRAMES)?;
+                triage::print(&triage);
+            }
+            PostAction::Summary => {
+                campaign::print(slice::from_ref(target), slice::from_ref(progress));
+            }
+            PostAction::Minimize => {
+                if progress.corpus.files == 0 {
+                    println!(
+                        "the corpus of {} is empty, nothing to minimize",
+                        target.binary
+                    );
+                    continue;
+                }
+                minimize::minimize(
+                    build_dir,
+                    &target.binary,
+                    &target.corpus.display().to_string(),
+                    None,
+                    &common.workspace(crate_root),
+                )?;
+            }
         }
-    };
+    }
+    Ok(())
+}
+
+/// Summarize fuzzing `target` since `started`, in seconds since the Unix epoch, replaying
+/// its new crashes through the debug build for their panic messages.
+fn fuzz_report(
+    common: &CommonOpts,
+    crate_root: &Path,
+    config: &TargetConfig,
+    build_dir: &BuildDir,
+    target: &campaign::Target,
+    progress: &campaign::Progress,
+    started: u64,
+) -> Result<report::TargetReport> {
+    let mut report = report::TargetReport::new(&target.binary, progress.time);
+    let properties = &mut report.properties;
+    properties.insert("runtime_secs", progress.time.as_secs());
+    // honggfuzz starts counting anew with every run, i.e. every slice of a campaign
+    let runs = stats::runs_since(
+        &stats::path(&common.workspace(crate_root), &target.binary),
+        started,
+    )?;
+    if let Some(last) = runs.last() {
+        properties.insert("iterations", runs.iter().map(|run| run.iterations).sum());
+        properties.insert("edges", last.edges);
+        properties.insert("blocks", last.blocks);
+    }
+    properties.insert("corpus_files", progress.corpus.files as u64);
+    properties.insert(
+        "new_corpus_files",
+        progress
+            .corpus
+            .files
+            .saturating_sub(progress.corpus_before.files) as u64,
+    );
+    properties.insert("new_crashes", progress.new_crashes() as u64);
+
+    let crashes = progress.new_crash_files(target)?;
+    if !crashes.is_empty() {
+        common.build(
+            crate_root,
+            &target.binary,
+            config,
+            BuildType::Debug,
+            build_dir,
+        )?;
+    }
+    for crash in crashes {
+        let outcome = match replay::replay(build_dir, &target.binary, &crash)? {
+            replay::Outcome::Pass => "not reproduced by the debug build".to_owned(),
+            outcome => outcome.to_string(),
+        };
+        report.crashes.push(report::Crash {
+            path: crash,
+            outcome,
+        });
+    }
+    Ok(report)
+}
 
-    cmd
+/// The current time in seconds since the Unix epoch.
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
 }
 
 fn hfuzz_run(
     launch: HonggfuzzLaunchArgs,
-    target_triple: &str,
+    config: &TargetConfig,
+    build_dir: &BuildDir,
     binary: &str,
-    args: impl IntoIterator<Item = impl ToString>,
     input: &str,
-    _crate_root: &Path,
-    _build_type: BuildType,
     workspace: &str,
-    target_dir: &str,
+    mutator: Option<&str>,
 ) -> Result<()> {
-    // add some flags to sanitizers to make them work with Rust code
-    let asan_options = env::var("ASAN_OPTIONS").unwrap_or_default();
-    let asan_options = "detect_odr_violation=0:".to_owned() + asan_options.as_str();
-
-    let tsan_options = env::var("TSAN_OPTIONS").unwrap_or_default();
-    let tsan_options = "report_signal_unsafe=0:".to_owned() + tsan_options.as_str();
+    let mut honggfuzz = honggfuzz_command(
+        &launch, config, build_dir, binary, input, workspace, mutator,
+    )?;
+    log::debug!("Exec: {:?}", honggfuzz);
 
-    // get user-defined args for honggfuzz
-    let hfuzz_run_args = env::var("HFUZZ_RUN_ARGS").unwrap_or_default();
-    log::debug!("HFUZZ_RUN_ARGS: {}", hfuzz_run_args);
-
-    // FIXME: we split by whitespace without respecting escaping or quotes
-    let hfuzz_run_args = hfuzz_run_args.split_whitespace();
+    // exec honggfuzz replacing current process
+    let err = honggfuzz.exec();
 
-    // get user-defined args for building
-    let hfuzz_build_args = env::var("HFUZZ_BUILD_ARGS").unwrap_or_default();
-    // FIXME: we split by whitespace without respecting escaping or quotes
-    let hfuzz_build_args = Vec::from_iter(hfuzz_build_args.split_whitespace());
+    Err(anyhow::Error::from(err).context(format!(
+        "Failed to execute {} \"cargo hfuzz build\" from fuzzed project directory",
+        build_dir.honggfuzz()
+    )))
+}
 
-    let hfuzz_build_profile = if let Some(arg) = hfuzz_build_args
-        .iter()
-        .find(|&&f| f.starts_with("--profile="))
+/// The honggfuzz command fuzzing `binary` with the corpus at `input`.
+fn honggfuzz_command(
+    launch: &HonggfuzzLaunchArgs,
+    config: &TargetConfig,
+    build_dir: &BuildDir,
+    binary: &str,
+    input: &str,
+    workspace: &str,
+    mutator: Option<&str>,
+) -> Result<Command> {
+    fs::create_dir_all(format!("{}/{}/input", &workspace, binary))?;
+    for dir in [&launch.output, &launch.crashdir, &launch.covdir_new]
+        .into_iter()
+        .flatten()
     {
-        arg.split("=")
-            .nth(1)
-            .expect("--profile not in correct format (eg. --profile=<label>)")
-    } else {
-        "release"
-    };
-
-    fs::create_dir_all(&format!("{}/{}/input", &workspace, binary.to_string()))?;
-
-    let command = format!("{}/honggfuzz", &target_dir);
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
 
     let mut arguments: Vec<String> = vec![
         "-W".to_owned(),
-        format!("{}/{}", &workspace, binary.to_string()),
+        format!("{}/{}", &workspace, binary),
         "-f".to_owned(),
         input.to_owned(),
         "-P".to_owned(),
     ];
-    arguments.extend(hfuzz_run_args.map(|x| x.to_string()));
-    arguments.extend(args.into_iter().map(|x| x.to_string()));
-
-    // exec honggfuzz replacing current process
-    let mut cmd = Command::new(&command);
-    cmd.env("ASAN_OPTIONS", asan_options)
-        .env("TSAN_OPTIONS", tsan_options);
-    if let Some(timeout) = launch.timeout {
-        arguments.extend(vec!["-t".to_owned(), timeout.as_secs().to_string()]);
-    }
-    if let Some(n) = launch.n_iterations {
-        arguments.extend(vec!["-N".to_owned(), n.to_string()]);
-    }
-    if launch.quietly {
-        arguments.push("--quietly".to_owned());
-    }
-    // if launch.verbose > 0 {
-    //     arguments.push("--verbose".to_owned());
-    // }
-    if let Some(exitcode) = launch.exit_upon_crash {
-        arguments.push("--exit_upon_crash".to_owned());
-        arguments.push("--exit_code_upon_crash".to_owned());
-        arguments.push(exitcode.to_string());
-    }
-    arguments.extend(
-        [
-            "--",
-            &format!(
-                "{}/{}/{}/{}",
-                &target_dir, target_triple, hfuzz_build_profile, &binary
-            ),
-        ]
-        .iter()
-        .map(ToString::to_string),
-    );
-
-    log::debug!("Exec: {} {}", &command, arguments.join(" "));
-
-    cmd.args(arguments).exec();
-
-    anyhow::bail!(
-        "Failed to execute {} \"cargo hfuzz build\" from fuzzed project directory",
-        &command
-    )
+    arguments.push("--statsfile".to_owned());
+    arguments.push(stats::path(workspace, binary).display().to_string());
+    arguments.extend(build_dir.honggfuzz_args());
+    // options given on the command line come last, so they take precedence
+    arguments.extend(config.honggfuzz_args());
+    arguments.extend(launch.honggfuzz_args());
+    if let Some(mutator) = mutator {
+        arguments.push("--mutate_cmd".to_owned());
+        arguments.push(mutator.to_owned());
+    }
+    arguments.push("--".to_owned());
+    arguments.push(build_dir.instrumented_binary(binary));
+
+    let mut honggfuzz = Command::new(build_dir.honggfuzz());
+    honggfuzz.envs(build_dir.sanitizer_env()).args(arguments);
+    if mutator.is_some() {
+        // honggfuzz hands an empty file to the mutator, which picks its input from the corpus
+        honggfuzz.env("HFUZZ_MUTATOR_CORPUS", input);
+    }
+    Ok(honggfuzz)
 }
 
 fn hfuzz_build(
-    binary: &str,
+    binaries: &[&str],
     extra_rustflags: &str,
+    build_args: &[String],
     args: impl IntoIterator<Item = impl ToString>,
     crate_root: &Path,
     build_type: BuildType,
-    target_dir: &str,
+    build_dir: &BuildDir,
 ) -> Result<()> {
-    let mut rustflags = "\
+    let mut rustflags = if build_type == BuildType::Mutator {
+        String::new()
+    } else {
+        "\
     --cfg fuzzing \
     -C debug-assertions \
     -C overflow_checks \
     "
-    .to_string();
+        .to_string()
+    };
 
-    let mut cargo_incremental = "1";
     match build_type {
+        BuildType::Mutator => {}
+
         BuildType::Debug => {
             rustflags.push_str(
                 "\
@@ -479,21 +1890,15 @@ fn hfuzz_build(
             );
         }
 
-        BuildType::ProfileWithGrcov => {
+        BuildType::Coverage => {
             rustflags.push_str(
                 "\
             --cfg fuzzing_debug \
-            -Zprofile \
-            -Cpanic=abort \
+            -C instrument-coverage \
             -C opt-level=0 \
             -C debuginfo=2 \
-            -Ccodegen-units=1 \
-            -Cinline-threshold=0 \
-            -Clink-dead-code \
             ",
             );
-            //-Coverflow-checks=off \
-            cargo_incremental = "0";
         }
 
         _ => {
@@ -509,7 +1914,7 @@ fn hfuzz_build(
                 // The fix for now is to pass `-C passes=sancov-module` only to compilers
                 // for which the LLVM version is >= 13.
                 let version_meta = rustc_version::version_meta().unwrap();
-                if version_meta.llvm_version.map_or(true, |v| v.major >= 13) {
+                if version_meta.llvm_version.is_none_or(|v| v.major >= 13) {
                     rustflags.push_str(
                         "\
                     -C passes=sancov-module \
@@ -552,46 +1957,43 @@ fn hfuzz_build(
     // add user provided flags
     rustflags.push_str(extra_rustflags);
 
-    // get user-defined args for building
-    let hfuzz_build_args = env::var("HFUZZ_BUILD_ARGS").unwrap_or_default();
-    log::debug!("HFUZZ_BUILD_ARGS: {}", hfuzz_build_args);
-
-    // FIXME: we split by whitespace without respecting escaping or quotes
-    let hfuzz_build_args = Vec::from_iter(hfuzz_build_args.split_whitespace());
-
     let cargo_bin = cargo_bin()?;
     let mut command = Command::new(&cargo_bin);
 
-    command.args(hfuzz_build_args.iter().map(|x| x.to_string()));
+    // user-defined args for building
+    command.args(build_args);
 
     // HACK to avoid building build scripts with rustflags
     command.arg("build");
-    command.arg("--bin");
-    command.arg(binary);
+    if build_dir.sanitizer.is_some_and(Sanitizer::needs_build_std) {
+        command.arg("-Zbuild-std");
+    }
+    for binary in binaries {
+        command.arg("--bin");
+        command.arg(binary);
+    }
     command.arg("--target");
-    command.arg(target_triple()?);
+    command.arg(&build_dir.target_triple);
 
     command
         .env("RUSTFLAGS", rustflags)
-        .env("CARGO_INCREMENTAL", cargo_incremental)
-        .env("CARGO_TARGET_DIR", &target_dir) // change target_dir to not clash with regular builds
-        .env("CRATE_ROOT", &crate_root);
+        .env("CARGO_INCREMENTAL", "1")
+        .env("CARGO_TARGET_DIR", &build_dir.path) // change target_dir to not clash with regular builds
+        .env("CRATE_ROOT", crate_root);
 
     // used by build.rs to check that versions are in sync
     // env variable to be read by build.rs script
     // to place honggfuzz executable at a known location
-    if build_type == BuildType::ProfileWithGrcov {
-        command
-            .env("CARGO_HONGGFUZZ_BUILD_VERSION", VERSION)
-            .env("CARGO_HONGGFUZZ_TARGET_DIR", &target_dir);
-    } else if build_type != BuildType::Debug {
-        if !hfuzz_build_args.iter().any(|f| f.starts_with("--profile")) {
+    if !matches!(build_type, BuildType::Debug | BuildType::Coverage) {
+        if !build_args.iter().any(|f| f.starts_with("--profile")) {
             command.arg("--release");
         }
 
-        command
-            .env("CARGO_HONGGFUZZ_BUILD_VERSION", VERSION)
-            .env("CARGO_HONGGFUZZ_TARGET_DIR", &target_dir);
+        if build_type != BuildType::Mutator {
+            command
+                .env("CARGO_HONGGFUZZ_BUILD_VERSION", VERSION)
+                .env("CARGO_HONGGFUZZ_TARGET_DIR", &build_dir.path);
+        }
     }
 
     command.args(args.into_iter().map(|x| x.to_string()));
@@ -599,7 +2001,7 @@ fn hfuzz_build(
     log::debug!(
         "Spawn: {} {}",
         command.get_program().to_string_lossy(),
-        Vec::from_iter(command.get_args().map(|x| x.to_string_lossy().to_owned()))
+        Vec::from_iter(command.get_args().map(|x| x.to_string_lossy().into_owned()))
             .as_slice()
             .join(" ")
     );
@@ -611,19 +2013,59 @@ fn hfuzz_build(
     Ok(())
 }
 
-fn hfuzz_clean(args: impl IntoIterator<Item = impl ToString>, target_dir: &str) -> Result<()> {
-    let cargo_bin = env::var("CARGO").unwrap();
-    let status = Command::new(cargo_bin)
-        .args(&["clean"])
-        .args(args.into_iter().map(|x| x.to_string()))
-        .env("CARGO_TARGET_DIR", target_dir) // change target_dir to not clash with regular builds
-        .status()?;
+/// Clean the fuzzing builds, of every selected package if any are given explicitly.
+fn hfuzz_clean(
+    args: impl IntoIterator<Item = impl ToString>,
+    manifest: &clap_cargo::Manifest,
+    packages: &clap_cargo::Workspace,
+) -> Result<()> {
+    let target_dir = env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| HONGGFUZZ_TARGET.into());
+    let args = Vec::from_iter(args.into_iter().map(|x| x.to_string()));
+
+    let mut commands = Vec::new();
+    if *packages == clap_cargo::Workspace::default() {
+        let mut command = Command::new(cargo_bin()?);
+        command.arg("clean");
+        let crate_root = match &manifest.manifest_path {
+            Some(manifest_path) => {
+                command.arg("--manifest-path").arg(manifest_path);
+                manifest_path
+                    .parent()
+                    .unwrap_or(Path::new("."))
+                    .to_path_buf()
+            }
+            None => PathBuf::from("."),
+        };
+        // change target_dir to not clash with regular builds
+        command.env("CARGO_TARGET_DIR", crate_root.join(&target_dir));
+        commands.push(command);
+    } else {
+        let metadata = metadata::load(manifest)?;
+        for package in metadata::selected_packages(&metadata, packages)? {
+            let mut command = Command::new(cargo_bin()?);
+            command
+                .arg("clean")
+                .arg("--manifest-path")
+                .arg(&package.manifest_path)
+                .arg("--package")
+                .arg(&package.name)
+                .env(
+                    "CARGO_TARGET_DIR",
+                    package.manifest_path.with_file_name(&target_dir),
+                );
+            commands.push(command);
+        }
+    }
 
-    if !status.success() {
-        anyhow::bail!(
-            "Process execution completed with exit code {:?}",
-            status.code()
-        )
+    for mut command in commands {
+        log::debug!("Spawn: {:?}", command);
+        let status = command.args(&args).status()?;
+        if !status.success() {
+            anyhow::bail!(
+                "Process execution completed with exit code {:?}",
+                status.code()
+            )
+        }
     }
 
     Ok(())
@@ -644,18 +2086,35 @@ fn main() -> Result<()> {
         args.extend(raw);
         args
     };
-    let opt = <Opt as clap::Parser>::parse_from(args);
+    let renamed = renamed_workspace_arg(&args);
+    let opt = parse_args(&args).unwrap_or_else(|err| {
+        let _ = err.print();
+        if let (true, Some(workspace)) = (err.use_stderr(), renamed) {
+            eprintln!("\n{}", renamed_workspace_note(workspace));
+        }
+        process::exit(err.exit_code())
+    });
     pretty_env_logger::formatted_timed_builder()
         .filter_level(opt.verbosity())
         .init();
+    if let Some(workspace) = renamed {
+        log::warn!("{}", renamed_workspace_note(workspace));
+    }
 
-    // change to crate root to have the same behavior as cargo build/run
-    let crate_root = find_crate_root().map_err(|e| {
-        e.context(anyhow::anyhow!(
-            "could not find `Cargo.toml` in current directory or any parent directory"
-        ))
-    })?;
-    env::set_current_dir(&crate_root).unwrap();
+    // change to crate root to have the same behavior as cargo build/run, a package selected
+    // explicitly is resolved through its metadata and a relative `--manifest-path` is
+    // relative to the current directory
+    let crate_root = if opt.command.selects_package() {
+        env::current_dir().context("Current directory is not set for process.")?
+    } else {
+        let crate_root = find_crate_root().map_err(|e| {
+            e.context(anyhow::anyhow!(
+                "could not find `Cargo.toml` in current directory or any parent directory"
+            ))
+        })?;
+        env::set_current_dir(&crate_root).unwrap();
+        crate_root
+    };
 
     opt.command.launch(crate_root)?;
     Ok(())
@@ -667,8 +2126,31 @@ mod tests {
     use assert_matches::assert_matches;
     use clap::Parser;
 
+    #[test]
+    fn renamed_workspace() {
+        let args = |cl: &str| Vec::from_iter(cl.split_ascii_whitespace().map(str::to_owned));
+        assert_eq!(
+            renamed_workspace_arg(&args("cargo-hongg fuzz --bin gameover --workspace=fuzz")),
+            Some("fuzz")
+        );
+        assert_eq!(
+            renamed_workspace_arg(&args("cargo-hongg fuzz --workspace fuzz --bin gameover")),
+            Some("fuzz")
+        );
+        assert_eq!(
+            renamed_workspace_arg(&args("cargo-hongg fuzz --workspace --all")),
+            None
+        );
+        assert_eq!(
+            renamed_workspace_arg(&args("cargo-hongg fuzz --bin gameover -- --workspace fuzz")),
+            None
+        );
+    }
+
     #[test]
     fn args() {
+        <Opt as clap::CommandFactory>::command().debug_assert();
+
         fn check(cl: &'static str) -> Opt {
             let args = Opt::parse_from(cl.split_ascii_whitespace());
             args
@@ -687,10 +2169,10 @@ mod tests {
             },
             ..
         } => {
-            assert_eq!(binary, "some-binary".to_owned());
+            assert_eq!(binary.as_deref(), Some("some-binary"));
             assert_matches!(common.verbosity.log_level(), Some(log::Level::Info));
             assert_eq!(launch.exit_upon_crash, Some(77));
-            assert_eq!(launch.quietly, true);
+            assert!(launch.quietly);
 
             assert_eq!(args.as_slice(), &["fff", "--xyz"]);
         });
@@ -710,5 +2192,379 @@ mod tests {
             assert_eq!(launch.exit_upon_crash, Some(0));
             assert_eq!(args.as_slice(), &["--xyz"]);
         });
+
+        assert_matches!(
+        check("cargo-hongg replay --bin gameover hfuzz_workspace/gameover crash.fuzz"),
+        Opt {
+            command: SubCommand::Replay {
+                binary,
+                inputs,
+                ..
+            },
+            ..
+        } => {
+            assert_eq!(binary, "gameover".to_owned());
+            assert_eq!(inputs, vec![PathBuf::from("hfuzz_workspace/gameover"), PathBuf::from("crash.fuzz")]);
+        });
+
+        assert_matches!(
+        check("cargo-hongg triage --bin gameover --frames 5"),
+        Opt {
+            command: SubCommand::Triage {
+                frames: 5,
+                crashes,
+                ..
+            },
+            ..
+        } => {
+            assert!(crashes.is_empty());
+        });
+
+        assert_matches!(
+        check("cargo-hongg export-tests --bin gameover --harness gameover::harness --triage"),
+        Opt {
+            command: SubCommand::ExportTests {
+                harness,
+                output,
+                triage: true,
+                ..
+            },
+            ..
+        } => {
+            assert_eq!(harness, "gameover::harness".to_owned());
+            assert_eq!(output, PathBuf::from("tests/fuzz_regressions.rs"));
+        });
+
+        assert_matches!(
+        check("cargo-hongg coverage --bin gameover -o cov"),
+        Opt {
+            command: SubCommand::Coverage {
+                binary,
+                input: None,
+                output,
+                ..
+            },
+            ..
+        } => {
+            assert_eq!(binary, "gameover".to_owned());
+            assert_eq!(output, Some(PathBuf::from("cov")));
+        });
+
+        assert_matches!(
+        check("cargo-hongg minimize --bin gameover --in-place"),
+        Opt {
+            command: SubCommand::Minimize {
+                binary,
+                input: None,
+                output: None,
+                in_place: true,
+                ..
+            },
+            ..
+        } => {
+            assert_eq!(binary, "gameover".to_owned());
+        });
+
+        assert_matches!(
+        check("cargo-hongg debug -b gameover --crash-file hfuzz_workspace/gameover/SIGABRT.fuzz --batch -d rust-gdb"),
+        Opt {
+            command: SubCommand::Debug {
+                debugger,
+                batch: true,
+                ..
+            },
+            ..
+        } => {
+            assert_eq!(debugger.as_deref(), Some("rust-gdb"));
+        });
+
+        assert_matches!(
+        check("cargo-hongg debug -b gameover -c crash.fuzz --gdbserver localhost:1234"),
+        Opt {
+            command: SubCommand::Debug {
+                debugger: None,
+                gdbserver: Some(address),
+                ..
+            },
+            ..
+        } => {
+            assert_eq!(address, "localhost:1234");
+        });
+        // `--debugger` may come from `HFUZZ_DEBUGGER`, it is unused with `--gdbserver`
+        assert!(Opt::try_parse_from(
+            "cargo-hongg debug -b gameover -c crash.fuzz --gdbserver localhost:1234 --debugger gdb"
+                .split_ascii_whitespace()
+        )
+        .is_ok());
+        assert!(Opt::try_parse_from(
+            "cargo-hongg debug -b gameover -c crash.fuzz --gdbserver localhost:1234 --batch"
+                .split_ascii_whitespace()
+        )
+        .is_err());
+
+        assert_matches!(
+        check("cargo-hongg dict --bin gameover -o gameover.dict"),
+        Opt {
+            command: SubCommand::Dict {
+                binary,
+                output: Some(output),
+                ..
+            },
+            ..
+        } => {
+            assert_eq!(binary, "gameover");
+            assert_eq!(output, PathBuf::from("gameover.dict"));
+        });
+
+        assert_matches!(
+        check("cargo-hongg fuzz --bins gameover,overgame --slice 60 --slices 4"),
+        Opt {
+            command: SubCommand::Fuzz {
+                binary: None,
+                bins,
+                slice,
+                slices,
+                ..
+            },
+            ..
+        } => {
+            assert_eq!(bins, vec!["gameover".to_owned(), "overgame".to_owned()]);
+            assert_eq!(slice.as_secs(), 60);
+            assert_eq!(slices, Some(4));
+        });
+
+        assert_matches!(
+            check("cargo-hongg fuzz --all-targets -p example --max-total-time 1h"),
+            Opt {
+                command: SubCommand::Fuzz {
+                    binary: None,
+                    all_targets: true,
+                    ..
+                },
+                ..
+            }
+        );
+        assert!(Opt::try_parse_from(
+            "cargo-hongg fuzz --all-targets --crashdir crashes".split_ascii_whitespace()
+        )
+        .is_err());
+        assert!(Opt::try_parse_from(
+            "cargo-hongg fuzz --bins gameover,overgame --output corpus".split_ascii_whitespace()
+        )
+        .is_err());
+
+        assert_matches!(
+        check("cargo-hongg tmin --bin gameover --crash-file hfuzz_workspace/gameover/SIGABRT.fuzz --debug"),
+        Opt {
+            command: SubCommand::Tmin {
+                crash_file,
+                debug: true,
+                ..
+            },
+            ..
+        } => {
+            assert_eq!(crash_file, PathBuf::from("hfuzz_workspace/gameover/SIGABRT.fuzz"));
+        });
+
+        assert_matches!(
+        check("cargo-hongg list -w fuzz -p parsers"),
+        Opt {
+            command: SubCommand::List {
+                hfuzz_workspace,
+                packages,
+                ..
+            },
+            ..
+        } => {
+            assert_eq!(hfuzz_workspace, "fuzz".to_owned());
+            assert_eq!(packages.package, vec!["parsers".to_owned()]);
+        });
+
+        assert_matches!(
+        check("cargo-hongg fuzz --bin gameover --manifest-path fuzz/Cargo.toml -F foo --no-default-features"),
+        Opt {
+            command: SubCommand::Fuzz {
+                common,
+                ..
+            },
+            ..
+        } => {
+            assert_eq!(common.manifest.manifest_path, Some(PathBuf::from("fuzz/Cargo.toml")));
+            assert_eq!(
+                common.cargo_args(Path::new("fuzz")),
+                vec!["--manifest-path", "fuzz/Cargo.toml", "--no-default-features", "--features", "foo"]
+            );
+        });
+
+        assert_matches!(
+        check("cargo-hongg fuzz --bin gameover --threads 4 --max-file-size 1024 --mutations-per-run 3 --rlimit-rss 2048 --crashdir crashes --save-all"),
+        Opt {
+            command: SubCommand::Fuzz {
+                launch,
+                ..
+            },
+            ..
+        } => {
+            assert_eq!(launch.target_config().threads, Some(4));
+            assert_eq!(launch.target_config().max_input_size, Some(1024));
+            // relative to the directory `cargo hongg` is invoked in
+            let crashdir = env::current_dir().unwrap().join("crashes").display().to_string();
+            assert_eq!(
+                launch.honggfuzz_args(),
+                vec!["--mutations_per_run", "3", "--rlimit_rss", "2048", "--crashdir", &crashdir, "--save_all"]
+            );
+        });
+
+        assert_matches!(
+        check("cargo-hongg fuzz --bin gameover --timeout 1500ms --max-total-time 2h30m --stop-after-plateau 20m"),
+        Opt {
+            command: SubCommand::Fuzz {
+                launch,
+                stop_after_plateau: Some(plateau),
+                ..
+            },
+            ..
+        } => {
+            assert_eq!(launch.target_config().timeout, Some(2));
+            assert_eq!(launch.honggfuzz_args(), vec!["--run_time", "9000"]);
+            assert_eq!(plateau.0, Duration::from_secs(20 * 60));
+        });
+
+        assert_matches!(
+        check("cargo-hongg fuzz --bin gameover --then triage,summary,minimize"),
+        Opt {
+            command: SubCommand::Fuzz {
+                supervise: false,
+                then,
+                ..
+            },
+            ..
+        } => {
+            assert_eq!(then, vec![PostAction::Triage, PostAction::Summary, PostAction::Minimize]);
+        });
+
+        assert_matches!(
+        check("cargo-hongg replay --bin gameover hfuzz_workspace/gameover --report-json out.json --report-junit out.xml"),
+        Opt {
+            command: SubCommand::Replay {
+                report_json: Some(json),
+                report_junit: Some(junit),
+                ..
+            },
+            ..
+        } => {
+            assert_eq!(json, PathBuf::from("out.json"));
+            assert_eq!(junit, PathBuf::from("out.xml"));
+        });
+
+        assert!(Opt::try_parse_from(
+            "cargo-hongg fuzz --bin gameover --max-total-time 2x".split_ascii_whitespace()
+        )
+        .is_err());
+        assert!(Opt::try_parse_from(
+            "cargo-hongg fuzz --bin gameover --threads 0".split_ascii_whitespace()
+        )
+        .is_err());
+        assert!(Opt::try_parse_from(
+            "cargo-hongg fuzz --bin gameover --dict does/not/exist.dict".split_ascii_whitespace()
+        )
+        .is_err());
+        // relative to the working directory rather than the crate root
+        assert_matches!(check("cargo-hongg fuzz --bin gameover --dict Cargo.toml"), Opt {
+            command: SubCommand::Fuzz { launch: HonggfuzzLaunchArgs { dict: Some(dict), .. }, .. },
+            ..
+        } => {
+            assert_eq!(dict, env::current_dir().unwrap().join("Cargo.toml"));
+        });
+
+        assert!(Opt::try_parse_from(
+            "cargo-hongg fuzz --all-targets --bin gameover".split_ascii_whitespace()
+        )
+        .is_err());
+        assert!(Opt::try_parse_from("cargo-hongg fuzz".split_ascii_whitespace()).is_err());
+        let parse = |cl: &str| {
+            parse_args(&Vec::from_iter(
+                cl.split_ascii_whitespace().map(str::to_owned),
+            ))
+        };
+        assert!(parse("cargo-hongg fuzz --bins gameover,overgame --slice 60").is_ok());
+        assert!(parse("cargo-hongg fuzz --bin gameover --input corpus").is_ok());
+        assert!(parse("cargo-hongg fuzz --all-targets --input corpus").is_err());
+        assert!(parse("cargo-hongg fuzz --bins gameover,overgame -i corpus").is_err());
+
+        assert!(Opt::try_parse_from(
+            "cargo-hongg minimize --bin gameover".split_ascii_whitespace()
+        )
+        .is_err());
+        assert!(Opt::try_parse_from(
+            "cargo-hongg minimize --bin gameover --in-place --output min".split_ascii_whitespace()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn build_dir() {
+        let opt = Opt::parse_from(
+            "cargo-hongg debug --sanitizer memory --target i686-unknown-linux-gnu -b gameover -c crash.fuzz"
+                .split_ascii_whitespace(),
+        );
+        let SubCommand::Debug { common, .. } = opt.command else {
+            panic!("expected the debug sub-command");
+        };
+        assert_eq!(common.sanitizer, Some(Sanitizer::Memory));
+
+        let build_dir = common.build_dir("hfuzz_target", common.sanitizer).unwrap();
+        assert_eq!(
+            build_dir.instrumented_binary("gameover"),
+            "hfuzz_target/memory/i686-unknown-linux-gnu/release/gameover"
+        );
+        assert_eq!(build_dir.honggfuzz_args(), vec!["--sanitizers"]);
+        assert_eq!(
+            Vec::from_iter(build_dir.sanitizer_env().iter().map(|(var, _)| *var)),
+            vec!["ASAN_OPTIONS", "TSAN_OPTIONS", "MSAN_OPTIONS"]
+        );
+
+        let build_dir = common.build_dir("hfuzz_target", None).unwrap();
+        assert_eq!(
+            build_dir.debug_binary("gameover"),
+            "hfuzz_target/i686-unknown-linux-gnu/debug/gameover"
+        );
+        assert_eq!(
+            Vec::from_iter(build_dir.sanitizer_env().iter().map(|(var, _)| *var)),
+            vec!["ASAN_OPTIONS", "TSAN_OPTIONS"]
+        );
+        assert!(build_dir.honggfuzz_args().is_empty());
+    }
+
+    #[test]
+    fn relative_manifest_path() {
+        // tests run in the directory of the package, a subdirectory of the workspace
+        let opt = Opt::parse_from(
+            "cargo-hongg list --manifest-path ../Cargo.toml".split_ascii_whitespace(),
+        );
+        assert!(opt.command.selects_package());
+        opt.command.launch(env::current_dir().unwrap()).unwrap();
+
+        let opt = Opt::parse_from(
+            "cargo-hongg stats --bin gameover --manifest-path ../Cargo.toml"
+                .split_ascii_whitespace(),
+        );
+        assert!(opt.command.selects_package());
+        let err = opt.command.launch(env::current_dir().unwrap()).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("no package in the workspace depends on"));
+
+        let opt = Opt::parse_from("cargo-hongg list".split_ascii_whitespace());
+        assert!(!opt.command.selects_package());
+    }
+
+    #[test]
+    fn shell_words() {
+        assert_eq!(
+            split_args(r#"--features "a b" --config 'x=1' -v"#).unwrap(),
+            vec!["--features", "a b", "--config", "x=1", "-v"]
+        );
+        assert!(split_args(r#"--features "a b"#).is_err());
     }
 }
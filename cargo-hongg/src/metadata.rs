@@ -0,0 +1,224 @@
+//! Discovery of fuzzing targets through `cargo metadata`.
+
+use crate::{cargo_bin, config, print_table, CorpusStats};
+use anyhow::Result;
+use cargo_metadata::{Metadata, Package};
+use std::path::{Path, PathBuf};
+
+/// Name of the library crate a package must depend on to contain fuzzing targets.
+const HONGG_CRATE: &str = "hongg";
+
+/// Maximum edit distance for a target name to be suggested on a typo.
+const MAX_SUGGESTION_DISTANCE: usize = 3;
+
+/// A binary target of a workspace package which depends on `hongg`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FuzzTarget {
+    pub(crate) name: String,
+    pub(crate) package: String,
+    pub(crate) src_path: PathBuf,
+    pub(crate) manifest_path: PathBuf,
+}
+
+impl FuzzTarget {
+    /// Directory of the package, the build and fuzzing data of the target are stored relative to it.
+    pub(crate) fn crate_root(&self) -> &Path {
+        self.manifest_path.parent().unwrap_or(Path::new("."))
+    }
+}
+
+/// Load the metadata of the workspace containing `manifest`, without resolving dependencies.
+pub(crate) fn load(manifest: &clap_cargo::Manifest) -> Result<Metadata> {
+    let metadata = manifest
+        .metadata()
+        .cargo_path(cargo_bin()?)
+        .no_deps()
+        .exec()?;
+    Ok(metadata)
+}
+
+/// Workspace packages selected with `--package`, `--workspace` and `--exclude`,
+/// all of them unless specific packages are given without `--workspace`.
+pub(crate) fn selected_packages<'m>(
+    metadata: &'m Metadata,
+    selection: &clap_cargo::Workspace,
+) -> Result<Vec<&'m Package>> {
+    let packages = metadata.workspace_packages();
+    for name in &selection.package {
+        if !packages.iter().any(|package| &package.name == name) {
+            anyhow::bail!("package `{}` not found in workspace", name);
+        }
+    }
+    // like cargo, `--workspace` selects all packages even if some are given with `--package`
+    let all = selection.workspace || selection.all || selection.package.is_empty();
+    Ok(Vec::from_iter(packages.into_iter().filter(|package| {
+        (all || selection.package.contains(&package.name))
+            && !selection.exclude.contains(&package.name)
+    })))
+}
+
+fn depends_on_hongg(package: &Package) -> bool {
+    package
+        .dependencies
+        .iter()
+        .any(|dependency| dependency.name == HONGG_CRATE)
+}
+
+/// All binary targets of the selected workspace packages which depend on `hongg`, except
+/// the ones configured as `mutators` of the package.
+pub(crate) fn fuzz_targets(
+    metadata: &Metadata,
+    selection: &clap_cargo::Workspace,
+) -> Result<Vec<FuzzTarget>> {
+    let mut targets = Vec::new();
+    for package in selected_packages(metadata, selection)? {
+        if !depends_on_hongg(package) {
+            continue;
+        }
+        let mutators = config::mutators(package)?;
+        targets.extend(
+            package
+                .targets
+                .iter()
+                .filter(|target| target.is_bin() && !mutators.contains(&target.name))
+                .map(|target| FuzzTarget {
+                    name: target.name.clone(),
+                    package: package.name.clone(),
+                    src_path: target.src_path.clone().into_std_path_buf(),
+                    manifest_path: package.manifest_path.clone().into_std_path_buf(),
+                }),
+        );
+    }
+    targets.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(targets)
+}
+
+/// Find the fuzzing target named `binary`, suggesting close matches if there is none.
+pub(crate) fn validate_binary<'t>(
+    targets: &'t [FuzzTarget],
+    binary: &str,
+) -> Result<&'t FuzzTarget> {
+    if let Some(target) = targets.iter().find(|target| target.name == binary) {
+        return Ok(target);
+    }
+    if targets.is_empty() {
+        anyhow::bail!(
+            "no binary target named `{}`, no package in the workspace depends on `{}`",
+            binary,
+            HONGG_CRATE
+        );
+    }
+
+    let mut suggestions = Vec::from_iter(targets.iter().filter_map(|target| {
+        let distance = strsim::levenshtein(binary, &target.name);
+        (distance <= MAX_SUGGESTION_DISTANCE).then_some((distance, target.name.as_str()))
+    }));
+    suggestions.sort();
+    match suggestions.first() {
+        Some((_, name)) => anyhow::bail!(
+            "no fuzzing target named `{}`, did you mean `{}`?",
+            binary,
+            name
+        ),
+        None => anyhow::bail!(
+            "no fuzzing target named `{}`, available targets: {}",
+            binary,
+            Vec::from_iter(targets.iter().map(|target| target.name.as_str())).join(", ")
+        ),
+    }
+}
+
+/// Print all fuzzing targets of the selected packages with the size of their corpus and
+/// crash directories, `workspace` is relative to the directory of each package.
+pub(crate) fn list(
+    metadata: &Metadata,
+    selection: &clap_cargo::Workspace,
+    workspace: &str,
+) -> Result<()> {
+    let targets = fuzz_targets(metadata, selection)?;
+    if targets.is_empty() {
+        println!(
+            "no binary targets found in packages depending on `{}`",
+            HONGG_CRATE
+        );
+        return Ok(());
+    }
+
+    let mut rows = vec![[
+        "TARGET".to_owned(),
+        "PACKAGE".to_owned(),
+        "SOURCE".to_owned(),
+        "CORPUS".to_owned(),
+        "CRASHES".to_owned(),
+    ]];
+    for target in targets {
+        let target_workspace = target.crate_root().join(workspace).join(&target.name);
+        let src_path = target
+            .src_path
+            .strip_prefix(&metadata.workspace_root)
+            .unwrap_or(&target.src_path)
+            .display()
+            .to_string();
+        rows.push([
+            target.name,
+            target.package,
+            src_path,
+            CorpusStats::collect(target_workspace.join("input"))?.to_string(),
+            CorpusStats::crashes(&target_workspace)?.to_string(),
+        ]);
+    }
+
+    print_table(rows);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(name: &str) -> FuzzTarget {
+        FuzzTarget {
+            name: name.to_owned(),
+            package: "example".to_owned(),
+            src_path: PathBuf::from("src/main.rs"),
+            manifest_path: PathBuf::from("Cargo.toml"),
+        }
+    }
+
+    #[test]
+    fn packages() {
+        let metadata = load(&clap_cargo::Manifest::default()).unwrap();
+        let selected = |selection: &clap_cargo::Workspace| {
+            let packages = selected_packages(&metadata, selection).unwrap();
+            let mut names = Vec::from_iter(packages.iter().map(|package| package.name.as_str()));
+            names.sort();
+            names.join(",")
+        };
+        let mut selection = clap_cargo::Workspace::default();
+        assert_eq!(selected(&selection), "cargo-hongg,hongg");
+        selection.package = vec!["hongg".to_owned()];
+        assert_eq!(selected(&selection), "hongg");
+        selection.workspace = true;
+        assert_eq!(selected(&selection), "cargo-hongg,hongg");
+        selection.exclude = vec!["hongg".to_owned()];
+        assert_eq!(selected(&selection), "cargo-hongg");
+    }
+
+    #[test]
+    fn suggestions() {
+        let targets = [target("example"), target("arbitrary-example")];
+        assert_eq!(validate_binary(&targets, "example").unwrap(), &targets[0]);
+
+        let err = validate_binary(&targets, "exmaple").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "no fuzzing target named `exmaple`, did you mean `example`?"
+        );
+
+        let err = validate_binary(&targets, "parser").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "no fuzzing target named `parser`, available targets: example, arbitrary-example"
+        );
+    }
+}
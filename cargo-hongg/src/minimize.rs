@@ -0,0 +1,61 @@
+//! Corpus minimization, distills the corpus down to the set of inputs
+//! which is required to reach the same coverage.
+
+use crate::{BuildDir, CorpusStats};
+use anyhow::Result;
+use fs_err as fs;
+use std::process::Command;
+
+/// Run honggfuzz in minimization mode on the corpus at `input`.
+///
+/// Writes the minimized corpus to `output` if given, otherwise the redundant
+/// files are removed from `input`.
+pub(crate) fn minimize(
+    build_dir: &BuildDir,
+    binary: &str,
+    input: &str,
+    output: Option<&str>,
+    workspace: &str,
+) -> Result<()> {
+    let before = CorpusStats::collect(input)?;
+    if before.files == 0 {
+        anyhow::bail!(
+            "Corpus \"{}\" is empty, there is nothing to minimize",
+            input
+        );
+    }
+
+    let command = build_dir.honggfuzz();
+
+    let mut arguments: Vec<String> = vec![
+        "--minimize".to_owned(),
+        "-W".to_owned(),
+        format!("{}/{}", workspace, binary),
+        "--input".to_owned(),
+        input.to_owned(),
+        "-P".to_owned(),
+    ];
+    arguments.extend(build_dir.honggfuzz_args());
+    if let Some(output) = output {
+        fs::create_dir_all(output)?;
+        arguments.push("--output".to_owned());
+        arguments.push(output.to_owned());
+    }
+    arguments.push("--".to_owned());
+    arguments.push(build_dir.instrumented_binary(binary));
+
+    log::debug!("Spawn: {} {}", &command, arguments.join(" "));
+
+    let status = Command::new(&command)
+        .envs(build_dir.sanitizer_env())
+        .args(arguments)
+        .status()?;
+    if !status.success() {
+        anyhow::bail!("Execution failed with status code {:?}", status.code());
+    }
+
+    let after = CorpusStats::collect(output.unwrap_or(input))?;
+    println!("before: {}", before);
+    println!("after:  {}", after);
+    Ok(())
+}
@@ -0,0 +1,253 @@
+//! Replaying inputs through the `fuzzing_debug` build of a target.
+
+use crate::BuildDir;
+use anyhow::Result;
+use fs_err as fs;
+use std::os::unix::process::ExitStatusExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+
+/// Exit code of a `fuzzing_debug` build if the input did not trigger a panic.
+const EXIT_CODE_NO_PANIC: i32 = 2;
+/// Exit code of the Rust runtime after an unwinding panic of the main thread.
+const EXIT_CODE_PANIC: i32 = 101;
+
+/// Observed behaviour of a target for a single input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Outcome {
+    /// The input did not trigger a panic.
+    Pass,
+    /// The target panicked.
+    Panic {
+        /// Source location of the panic, i.e. `src/main.rs:21:13`.
+        location: Option<String>,
+        message: String,
+    },
+    /// The target was terminated by a signal, i.e. due to an abort or a segfault.
+    Signal(i32),
+    /// The target exited with an unexpected code, i.e. a sanitizer reported an error.
+    Exit(i32),
+}
+
+impl Outcome {
+    /// Whether the input crashed the target.
+    pub(crate) fn is_crash(&self) -> bool {
+        !matches!(self, Self::Pass)
+    }
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pass => write!(f, "pass"),
+            Self::Panic { location, message } => write!(
+                f,
+                "panic at {}: {}",
+                location.as_deref().unwrap_or("<unknown>"),
+                message
+            ),
+            Self::Signal(signal) => write!(f, "signal {}", signal_name(*signal)),
+            Self::Exit(code) => write!(f, "exit code {}", code),
+        }
+    }
+}
+
+/// Name of the common signals terminating a fuzzing target.
+fn signal_name(signal: i32) -> String {
+    match signal {
+        4 => "SIGILL".to_owned(),
+        5 => "SIGTRAP".to_owned(),
+        6 => "SIGABRT".to_owned(),
+        7 => "SIGBUS".to_owned(),
+        8 => "SIGFPE".to_owned(),
+        9 => "SIGKILL".to_owned(),
+        11 => "SIGSEGV".to_owned(),
+        signal => signal.to_string(),
+    }
+}
+
+/// The files given in `paths` and the ones directly within the given directories,
+/// skipping hidden files, the report and stats of honggfuzz, saved backtraces and dictionaries.
+pub(crate) fn collect_inputs(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut inputs = Vec::new();
+    for path in paths {
+        if path.is_file() {
+            inputs.push(path.clone());
+        } else if path.is_dir() {
+            let mut files = Vec::new();
+            for entry in fs::read_dir(path)? {
+                let entry = entry?;
+                if entry.file_type()?.is_file() && is_input(&entry.file_name().to_string_lossy()) {
+                    files.push(entry.path());
+                }
+            }
+            files.sort();
+            inputs.extend(files);
+        } else {
+            anyhow::bail!("\"{}\" is neither a file nor a directory", path.display());
+        }
+    }
+    Ok(inputs)
+}
+
+/// Whether the file named `name` within a corpus or crash directory is an input, with the
+/// same rules as `hongg::replay::is_input`, which `hongg::replay_dir!` applies.
+fn is_input(name: &str) -> bool {
+    !name.starts_with('.')
+        && name != "HONGGFUZZ.REPORT.TXT"
+        && !name.ends_with(".backtrace")
+        && !name.ends_with(".dict")
+        && !name.ends_with(".csv")
+}
+
+/// Replay all `inputs` through the `fuzzing_debug` build of `binary`, printing the outcome of each.
+pub(crate) fn replay_all(
+    build_dir: &BuildDir,
+    binary: &str,
+    inputs: &[PathBuf],
+) -> Result<Vec<(PathBuf, Outcome)>> {
+    let mut outcomes = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let outcome = replay(build_dir, binary, input)?;
+        println!("{}: {}", input.display(), outcome);
+        outcomes.push((input.clone(), outcome));
+    }
+    Ok(outcomes)
+}
+
+/// Replay a single `input` through the `fuzzing_debug` build of `binary`.
+pub(crate) fn replay(build_dir: &BuildDir, binary: &str, input: &Path) -> Result<Outcome> {
+    Ok(run(build_dir, binary, input, false)?.0)
+}
+
+/// Replay a single `input` like [`replay`], but also return the output of the target
+/// on stderr, which contains the backtrace of a panic.
+pub(crate) fn replay_with_backtrace(
+    build_dir: &BuildDir,
+    binary: &str,
+    input: &Path,
+) -> Result<(Outcome, String)> {
+    run(build_dir, binary, input, true)
+}
+
+fn run(
+    build_dir: &BuildDir,
+    binary: &str,
+    input: &Path,
+    backtrace: bool,
+) -> Result<(Outcome, String)> {
+    let output = Command::new(build_dir.debug_binary(binary))
+        .envs(build_dir.sanitizer_env())
+        .env("CARGO_HONGGFUZZ_CRASH_FILENAME", input)
+        .env("RUST_BACKTRACE", if backtrace { "1" } else { "0" })
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    let outcome = outcome(input, output.status, &stderr)?;
+    Ok((outcome, stderr))
+}
+
+/// The outcome of replaying `input` through a `fuzzing_debug` build which exited with
+/// `status` and wrote `stderr`.
+pub(crate) fn outcome(input: &Path, status: ExitStatus, stderr: &str) -> Result<Outcome> {
+    Ok(match (status.code(), status.signal()) {
+        (Some(EXIT_CODE_NO_PANIC), _) => Outcome::Pass,
+        (Some(EXIT_CODE_PANIC), _) => {
+            let (location, message) = parse_panic(stderr).unwrap_or_default();
+            Outcome::Panic { location, message }
+        }
+        (_, Some(signal)) => Outcome::Signal(signal),
+        (Some(code), _) if code != 0 => Outcome::Exit(code),
+        (code, _) => anyhow::bail!(
+            "Replaying \"{}\" failed with unexpected exit code {:?}, is `fuzz!` called? {}",
+            input.display(),
+            code,
+            stderr.trim()
+        ),
+    })
+}
+
+/// Extract the location and message of the first panic from the
+/// output of the default panic hook.
+///
+/// Supports the message first format of older toolchains
+/// `thread 'main' panicked at 'BOOM', src/main.rs:21:13`
+/// as well as the current one, which puts the message on the following lines
+/// `thread 'main' panicked at src/main.rs:21:13:\nBOOM`.
+pub(crate) fn parse_panic(stderr: &str) -> Option<(Option<String>, String)> {
+    let mut lines = stderr.lines();
+    let rest = lines.find_map(|line| {
+        line.split_once(" panicked at ")
+            .map(|(_thread, rest)| rest.trim_end())
+    })?;
+
+    if let Some(quoted) = rest.strip_prefix('\'') {
+        let (message, location) = match quoted.rsplit_once("', ") {
+            Some((message, location)) => (message, Some(location.to_owned())),
+            None => (quoted.trim_end_matches('\''), None),
+        };
+        return Some((location, message.to_owned()));
+    }
+
+    let location = rest.trim_end_matches(':').to_owned();
+    let message =
+        Vec::from_iter(lines.take_while(|line| {
+            !line.starts_with("note: ") && !line.starts_with("stack backtrace:")
+        }))
+        .join("\n");
+    Some((Some(location), message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panic_message() {
+        assert_eq!(
+            parse_panic(
+                "thread 'main' (3690) panicked at src/main.rs:21:13:\nBOOM\nnote: run with `RUST_BACKTRACE=1`"
+            ),
+            Some((Some("src/main.rs:21:13".to_owned()), "BOOM".to_owned()))
+        );
+        assert_eq!(
+            parse_panic("thread 'main' panicked at 'BOOM', src/main.rs:21:13\n"),
+            Some((Some("src/main.rs:21:13".to_owned()), "BOOM".to_owned()))
+        );
+        assert_eq!(parse_panic("error: failed to open \"x\""), None);
+    }
+
+    #[test]
+    fn outcome() {
+        let panic = Outcome::Panic {
+            location: Some("src/main.rs:21:13".to_owned()),
+            message: "BOOM".to_owned(),
+        };
+        assert_eq!(panic.to_string(), "panic at src/main.rs:21:13: BOOM");
+        assert_eq!(Outcome::Signal(11).to_string(), "signal SIGSEGV");
+        assert!(!Outcome::Pass.is_crash());
+        assert!(Outcome::Signal(6).is_crash());
+        assert_eq!(Outcome::Exit(1).to_string(), "exit code 1");
+        assert!(Outcome::Exit(1).is_crash());
+
+        let input = Path::new("input");
+        let status = |raw| ExitStatus::from_raw(raw);
+        assert_eq!(
+            super::outcome(input, status(2 << 8), "").unwrap(),
+            Outcome::Pass
+        );
+        // the default exit code of ASan
+        assert_eq!(
+            super::outcome(input, status(1 << 8), "").unwrap(),
+            Outcome::Exit(1)
+        );
+        assert_eq!(
+            super::outcome(input, status(6), "").unwrap(),
+            Outcome::Signal(6)
+        );
+        assert!(super::outcome(input, status(0), "").is_err());
+    }
+}
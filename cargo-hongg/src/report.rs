@@ -0,0 +1,182 @@
+//! Machine-readable summaries of fuzzing and replay runs, as JSON and as JUnit XML for CI,
+//! with one test case per target which fails if the target crashed.
+
+use anyhow::Result;
+use fs_err as fs;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// The summary of a run of `cargo hongg`.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Report {
+    /// The sub-command which ran, like `fuzz` or `replay`.
+    pub(crate) suite: String,
+    pub(crate) targets: Vec<TargetReport>,
+}
+
+/// The summary of a single target.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct TargetReport {
+    pub(crate) target: String,
+    /// Wall-clock time spent on the target, in seconds.
+    pub(crate) time: f64,
+    /// Counters like the number of iterations or the coverage, as far as they are known.
+    pub(crate) properties: BTreeMap<&'static str, u64>,
+    /// The new crashes of a fuzzing run, or the crashing inputs of a replay.
+    pub(crate) crashes: Vec<Crash>,
+}
+
+/// A crashing input and how it crashed.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Crash {
+    pub(crate) path: PathBuf,
+    /// The outcome of replaying the input, like the panic message.
+    pub(crate) outcome: String,
+}
+
+impl TargetReport {
+    pub(crate) fn new(target: &str, time: Duration) -> Self {
+        Self {
+            target: target.to_owned(),
+            time: time.as_secs_f64(),
+            properties: BTreeMap::new(),
+            crashes: Vec::new(),
+        }
+    }
+
+    fn failed(&self) -> bool {
+        !self.crashes.is_empty()
+    }
+}
+
+impl Report {
+    /// Write the report as JSON to `json` and as JUnit XML to `junit`, if given.
+    pub(crate) fn write(&self, json: Option<&Path>, junit: Option<&Path>) -> Result<()> {
+        if let Some(json) = json {
+            fs::write(json, serde_json::to_string_pretty(self)?)?;
+            println!("JSON report written to {}", json.display());
+        }
+        if let Some(junit) = junit {
+            fs::write(junit, self.junit()?)?;
+            println!("JUnit report written to {}", junit.display());
+        }
+        Ok(())
+    }
+
+    fn junit(&self) -> Result<String> {
+        let name = format!("cargo-hongg {}", self.suite);
+        let tests = self.targets.len();
+        let failures = self.targets.iter().filter(|target| target.failed()).count();
+        let time = self.targets.iter().map(|target| target.time).sum::<f64>();
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        writeln!(
+            xml,
+            r#"<testsuites name="{name}" tests="{tests}" failures="{failures}" time="{time:.3}">"#,
+            name = escape(&name),
+        )?;
+        writeln!(
+            xml,
+            r#"  <testsuite name="{name}" tests="{tests}" failures="{failures}" errors="0" skipped="0" time="{time:.3}">"#,
+            name = escape(&name),
+        )?;
+        for target in &self.targets {
+            writeln!(
+                xml,
+                r#"    <testcase name="{}" classname="{}" time="{:.3}">"#,
+                escape(&target.target),
+                escape(&name),
+                target.time
+            )?;
+            if !target.properties.is_empty() {
+                writeln!(xml, "      <properties>")?;
+                for (property, value) in &target.properties {
+                    writeln!(
+                        xml,
+                        r#"        <property name="{}" value="{}"/>"#,
+                        property, value
+                    )?;
+                }
+                writeln!(xml, "      </properties>")?;
+            }
+            if target.failed() {
+                let body = Vec::from_iter(
+                    target
+                        .crashes
+                        .iter()
+                        .map(|crash| format!("{}: {}", crash.path.display(), crash.outcome)),
+                )
+                .join("\n");
+                writeln!(
+                    xml,
+                    r#"      <failure message="crashing inputs: {}" type="crash">{}</failure>"#,
+                    target.crashes.len(),
+                    escape(&body)
+                )?;
+            }
+            writeln!(xml, "    </testcase>")?;
+        }
+        writeln!(xml, "  </testsuite>")?;
+        writeln!(xml, "</testsuites>")?;
+        Ok(xml)
+    }
+}
+
+/// Escape `text` for XML attributes and content, dropping the control characters
+/// which are not allowed in XML at all.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn junit() {
+        let mut passed = TargetReport::new("parse", Duration::from_secs(60));
+        passed.properties.insert("iterations", 1000);
+        let mut failed = TargetReport::new("decode", Duration::from_millis(1500));
+        failed.crashes.push(Crash {
+            path: PathBuf::from("hfuzz_workspace/decode/SIGABRT.fuzz"),
+            outcome: "panic at src/main.rs:3:5: len < \"4\"\u{1b}".to_owned(),
+        });
+        let report = Report {
+            suite: "fuzz".to_owned(),
+            targets: vec![passed, failed],
+        };
+        assert_eq!(
+            report.junit().unwrap(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="cargo-hongg fuzz" tests="2" failures="1" time="61.500">
+  <testsuite name="cargo-hongg fuzz" tests="2" failures="1" errors="0" skipped="0" time="61.500">
+    <testcase name="parse" classname="cargo-hongg fuzz" time="60.000">
+      <properties>
+        <property name="iterations" value="1000"/>
+      </properties>
+    </testcase>
+    <testcase name="decode" classname="cargo-hongg fuzz" time="1.500">
+      <failure message="crashing inputs: 1" type="crash">hfuzz_workspace/decode/SIGABRT.fuzz: panic at src/main.rs:3:5: len &lt; &quot;4&quot;</failure>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+        );
+    }
+}
@@ -0,0 +1,75 @@
+//! Sanitizers supported by `rustc`, see the
+//! [unstable book](https://doc.rust-lang.org/unstable-book/compiler-flags/sanitizer.html).
+
+use std::env;
+
+/// A sanitizer to build the fuzzing target with, requires a nightly toolchain.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, serde::Deserialize, serde::Serialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Sanitizer {
+    Address,
+    Thread,
+    Leak,
+    Memory,
+}
+
+impl Sanitizer {
+    /// Name as given to `-Z sanitizer`, also used as name of the build directory.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Address => "address",
+            Self::Thread => "thread",
+            Self::Leak => "leak",
+            Self::Memory => "memory",
+        }
+    }
+
+    /// Flags given to `rustc` to enable the sanitizer.
+    pub(crate) fn rustflags(self) -> String {
+        let mut rustflags = format!("-Z sanitizer={}", self.name());
+        if self == Self::Memory {
+            rustflags.push_str(" -Z sanitizer-memory-track-origins");
+        }
+        rustflags
+    }
+
+    /// Memory sanitizer reports false positives for any uninstrumented code,
+    /// so the standard library has to be rebuilt with it.
+    pub(crate) fn needs_build_std(self) -> bool {
+        self == Self::Memory
+    }
+
+    /// The options variable of the sanitizer runtime and our defaults for it.
+    fn options(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Address => ("ASAN_OPTIONS", "abort_on_error=1:detect_odr_violation=0:"),
+            Self::Thread => ("TSAN_OPTIONS", "abort_on_error=1:report_signal_unsafe=0:"),
+            Self::Leak => ("LSAN_OPTIONS", "abort_on_error=1:"),
+            Self::Memory => ("MSAN_OPTIONS", "abort_on_error=1:"),
+        }
+    }
+}
+
+/// Environment for running the binaries, adds some flags to sanitizers to make them work
+/// with Rust code. The address and thread sanitizer options are always set, as code linked
+/// into an uninstrumented binary may still be built with them.
+pub(crate) fn runtime_env(sanitizer: Option<Sanitizer>) -> Vec<(&'static str, String)> {
+    let mut options = vec![
+        ("ASAN_OPTIONS", "detect_odr_violation=0:"),
+        ("TSAN_OPTIONS", "report_signal_unsafe=0:"),
+    ];
+    if let Some(sanitizer) = sanitizer {
+        let (var, defaults) = sanitizer.options();
+        options.retain(|(other, _)| *other != var);
+        options.push((var, defaults));
+    }
+    // our defaults come first so the ones already set in the environment take precedence
+    Vec::from_iter(options.into_iter().map(|(var, defaults)| {
+        (
+            var,
+            defaults.to_owned() + &env::var(var).unwrap_or_default(),
+        )
+    }))
+}
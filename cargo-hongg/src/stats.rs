@@ -0,0 +1,255 @@
+//! Statistics of a fuzzing run, which honggfuzz appends to its `--statsfile` every second.
+
+use anyhow::{Context, Result};
+use fs_err as fs;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Name of the stats file in the workspace of a target.
+const FILE_NAME: &str = "stats.csv";
+
+/// Number of comma separated values of a line, see [`Stats`] for their order.
+const FIELDS: usize = 9;
+
+/// How often the stats file is checked for new lines when following it.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The stats file of `binary` in the workspace, honggfuzz writes to it during `cargo hongg fuzz`.
+pub(crate) fn path(workspace: &str, binary: &str) -> PathBuf {
+    Path::new(workspace).join(binary).join(FILE_NAME)
+}
+
+/// One line of the stats file, all counters start from 0 with every run of honggfuzz.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub(crate) struct Stats {
+    /// Time the line was written, in seconds since the Unix epoch.
+    pub(crate) unix_time: u64,
+    /// Time an input last reached new coverage, in seconds since the Unix epoch.
+    pub(crate) last_coverage_update: u64,
+    pub(crate) iterations: u64,
+    /// Average number of executions per second.
+    pub(crate) execs_per_sec: u64,
+    pub(crate) crashes: u64,
+    pub(crate) unique_crashes: u64,
+    pub(crate) timeouts: u64,
+    pub(crate) edges: u64,
+    pub(crate) blocks: u64,
+}
+
+impl Stats {
+    /// Parse a line of values in the order of the header written by honggfuzz,
+    /// `unix_time, last_cov_update, total_exec, exec_per_sec, crashes, unique_crashes, hangs, edge_cov, block_cov`.
+    fn parse(line: &str) -> Result<Self> {
+        let values = line
+            .split(',')
+            .map(|value| value.trim().parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let [unix_time, last_coverage_update, iterations, execs_per_sec, crashes, unique_crashes, timeouts, edges, blocks] =
+            <[u64; FIELDS]>::try_from(values).map_err(|values| {
+                anyhow::anyhow!("expected {} values, found {}", FIELDS, values.len())
+            })?;
+        Ok(Self {
+            unix_time,
+            last_coverage_update,
+            iterations,
+            execs_per_sec,
+            crashes,
+            unique_crashes,
+            timeouts,
+            edges,
+            blocks,
+        })
+    }
+
+    /// Print the stats as a multi-line summary.
+    fn print_summary(&self, binary: &str) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        println!("target:         {}", binary);
+        println!(
+            "updated:        {} ({} ago)",
+            timestamp(self.unix_time),
+            since(self.unix_time, now)
+        );
+        println!(
+            "iterations:     {} ({}/s)",
+            self.iterations, self.execs_per_sec
+        );
+        println!(
+            "coverage:       {} edges, {} blocks, last new coverage {} ago",
+            self.edges,
+            self.blocks,
+            since(self.last_coverage_update, now)
+        );
+        println!(
+            "crashes:        {} ({} unique)",
+            self.crashes, self.unique_crashes
+        );
+        println!("timeouts:       {}", self.timeouts);
+    }
+
+    /// Print the stats on one line, while following a running campaign.
+    fn print_line(&self) {
+        println!(
+            "{}  {} iterations ({}/s), {} edges, {} crashes ({} unique), {} timeouts",
+            timestamp(self.unix_time),
+            self.iterations,
+            self.execs_per_sec,
+            self.edges,
+            self.crashes,
+            self.unique_crashes,
+            self.timeouts
+        );
+    }
+}
+
+/// Parse all lines of a stats file, which holds a header line for every run of honggfuzz.
+fn parse(content: &str) -> Result<Vec<Stats>> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|(index, line)| {
+            Stats::parse(line).with_context(|| format!("Malformed line {}", index + 1))
+        })
+        .collect()
+}
+
+/// The last line of every run of honggfuzz in a stats file, i.e. its final counters.
+fn runs(content: &str) -> Result<Vec<Stats>> {
+    let mut runs = Vec::new();
+    let mut last = None;
+    for (index, line) in content.lines().enumerate() {
+        if line.starts_with('#') {
+            runs.extend(last.take());
+        } else if !line.trim().is_empty() {
+            last =
+                Some(Stats::parse(line).with_context(|| format!("Malformed line {}", index + 1))?);
+        }
+    }
+    runs.extend(last);
+    Ok(runs)
+}
+
+/// The final counters of the runs of honggfuzz which wrote to the stats file at `path`
+/// since `start`, in seconds since the Unix epoch.
+pub(crate) fn runs_since(path: &Path, start: u64) -> Result<Vec<Stats>> {
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+    let mut runs = runs(complete_lines(&fs::read_to_string(path)?))?;
+    runs.retain(|stats| stats.unix_time >= start);
+    Ok(runs)
+}
+
+/// The lines of `content` up to the last newline, honggfuzz may be in the middle of
+/// writing the last line.
+fn complete_lines(content: &str) -> &str {
+    &content[..content.rfind('\n').map_or(0, |end| end + 1)]
+}
+
+fn timestamp(unix_time: u64) -> humantime::Rfc3339Timestamp {
+    humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(unix_time))
+}
+
+fn since(unix_time: u64, now: u64) -> humantime::FormattedDuration {
+    humantime::format_duration(Duration::from_secs(now.saturating_sub(unix_time)))
+}
+
+fn print(stats: &Stats, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string(stats)?);
+    } else {
+        stats.print_line();
+    }
+    Ok(())
+}
+
+/// Print the latest stats of `binary` from the stats file at `path`, and with `follow`
+/// every line appended to it until interrupted.
+pub(crate) fn stats(binary: &str, path: &Path, json: bool, follow: bool) -> Result<()> {
+    if !follow {
+        let content = fs::read_to_string(path).with_context(|| {
+            format!(
+                "No stats of `{}` yet, they are written while `cargo hongg fuzz` runs",
+                binary
+            )
+        })?;
+        let Some(latest) = parse(complete_lines(&content))?.pop() else {
+            anyhow::bail!("The stats file {} is empty", path.display());
+        };
+        if json {
+            println!("{}", serde_json::to_string_pretty(&latest)?);
+        } else {
+            latest.print_summary(binary);
+        }
+        return Ok(());
+    }
+
+    // honggfuzz appends to the file, a new run is a new header followed by new lines
+    let mut printed: Option<usize> = None;
+    loop {
+        if path.is_file() {
+            let all = parse(complete_lines(&fs::read_to_string(path)?))?;
+            // start with the latest line rather than the whole history
+            let mut from = printed.unwrap_or(all.len().saturating_sub(1));
+            if from > all.len() {
+                // the file was removed and written anew
+                from = 0;
+            }
+            for stats in &all[from..] {
+                print(stats, json)?;
+            }
+            printed = Some(all.len());
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_stats() {
+        let content = "\
+# unix_time, last_cov_update, total_exec, exec_per_sec, crashes, unique_crashes, hangs, edge_cov, block_cov
+1760000000, 1759999990, 1000, 500, 3, 1, 0, 120, 80
+1760000001, 1760000001, 1600, 600, 4, 2, 1, 125, 82
+# unix_time, last_cov_update, total_exec, exec_per_sec, crashes, unique_crashes, hangs, edge_cov, block_cov
+1760000100, 1760000100, 10, 10, 0, 0, 0, 40, 30
+";
+        let all = parse(content).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(
+            all[1],
+            Stats {
+                unix_time: 1760000001,
+                last_coverage_update: 1760000001,
+                iterations: 1600,
+                execs_per_sec: 600,
+                crashes: 4,
+                unique_crashes: 2,
+                timeouts: 1,
+                edges: 125,
+                blocks: 82,
+            }
+        );
+        assert_eq!(
+            Vec::from_iter(runs(content).unwrap().iter().map(|stats| stats.iterations)),
+            vec![1600, 10]
+        );
+        assert!(parse("1, 2, 3\n").is_err());
+        assert_eq!(
+            parse(complete_lines(&format!("{}1760000200, 17600", content)))
+                .unwrap()
+                .len(),
+            3
+        );
+        assert!(parse("1, 2, 3, 4, 5, 6, 7, 8, x\n").is_err());
+    }
+}
@@ -0,0 +1,248 @@
+//! Run honggfuzz as a child process rather than replacing `cargo hongg` with it, to stop
+//! fuzzing once the coverage stopped growing and to act on the results once honggfuzz exited.
+//!
+//! Honggfuzz runs in its own process group, so a `Ctrl-C` in the terminal reaches only
+//! `cargo hongg`, which forwards SIGINT and SIGTERM to honggfuzz exactly once and then
+//! waits for it to exit cleanly.
+
+use crate::{stats, unix_time};
+use anyhow::{Context, Result};
+use fs_err as fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often honggfuzz is checked for having exited and for signals to forward.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How often the stats file is checked for new coverage, honggfuzz updates it every second.
+const STATS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Name of the current log file in the log directory, rotated ones get a numeric suffix.
+const LOG_NAME: &str = "honggfuzz.log";
+
+/// A log file is rotated once it grew beyond this size.
+const MAX_LOG_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Number of rotated log files kept besides the current one.
+const MAX_ROTATED_LOGS: usize = 4;
+
+/// The termination signal received while supervising honggfuzz, 0 if there was none.
+static SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+extern "C" fn record_signal(signal: libc::c_int) {
+    SIGNAL.store(signal, Ordering::SeqCst);
+}
+
+/// Records SIGINT and SIGTERM while installed, the previous handlers are restored on drop
+/// so a signal outside of supervision terminates `cargo hongg` as usual.
+struct SignalHandlers {
+    previous: [(libc::c_int, libc::sighandler_t); 2],
+}
+
+impl SignalHandlers {
+    fn install() -> Self {
+        let handler = record_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        // SAFETY: the handler only stores to an atomic, which is async-signal-safe
+        let previous = [libc::SIGINT, libc::SIGTERM]
+            .map(|signal| (signal, unsafe { libc::signal(signal, handler) }));
+        Self { previous }
+    }
+}
+
+impl Drop for SignalHandlers {
+    fn drop(&mut self) {
+        for (signal, handler) in self.previous {
+            // SAFETY: the handler was installed before, by us or by the runtime
+            unsafe {
+                libc::signal(signal, handler);
+            }
+        }
+    }
+}
+
+/// Whether `cargo hongg` was asked to terminate while supervising honggfuzz.
+pub(crate) fn interrupted() -> bool {
+    SIGNAL.load(Ordering::SeqCst) != 0
+}
+
+/// How to supervise a run of honggfuzz.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Supervision {
+    /// The `--statsfile` of honggfuzz.
+    pub(crate) stats: PathBuf,
+    /// Stop honggfuzz once no input reached new coverage for this long, according to the
+    /// time of the last coverage update in the `stats`.
+    pub(crate) plateau: Option<Duration>,
+    /// Directory to copy the output of honggfuzz to, besides the terminal.
+    pub(crate) logs: Option<PathBuf>,
+}
+
+/// The log directory of `binary` in the workspace.
+pub(crate) fn logs(workspace: &str, binary: &str) -> PathBuf {
+    Path::new(workspace).join(binary).join("logs")
+}
+
+/// Run `honggfuzz` until it exits, forwarding termination signals to it.
+pub(crate) fn run(mut honggfuzz: Command, supervision: &Supervision) -> Result<ExitStatus> {
+    let _handlers = SignalHandlers::install();
+    honggfuzz.process_group(0);
+    let log = match &supervision.logs {
+        Some(logs) => {
+            honggfuzz.stdout(Stdio::piped()).stderr(Stdio::piped());
+            Some(Arc::new(Mutex::new(RotatingLog::create(logs)?)))
+        }
+        None => None,
+    };
+
+    log::debug!("Spawn: {:?}", honggfuzz);
+    let mut child = honggfuzz
+        .spawn()
+        .with_context(|| format!("Failed to run {:?}", honggfuzz.get_program()))?;
+    let mut tees = Vec::new();
+    if let Some(log) = &log {
+        if let Some(stdout) = child.stdout.take() {
+            tees.push(tee(stdout, io::stdout, Arc::clone(log)));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tees.push(tee(stderr, io::stderr, Arc::clone(log)));
+        }
+    }
+
+    let status = watch(&mut child, supervision)?;
+    for tee in tees {
+        if let Ok(Err(err)) = tee.join() {
+            log::warn!("Failed to write the log of honggfuzz: {}", err);
+        }
+    }
+    Ok(status)
+}
+
+/// Wait for `child` to exit, forwarding signals and watching the stats for a plateau.
+fn watch(child: &mut Child, supervision: &Supervision) -> Result<ExitStatus> {
+    // the stats file keeps the lines of earlier runs
+    let started = unix_time();
+    let mut last_check = Instant::now();
+    let mut forwarded = false;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        thread::sleep(POLL_INTERVAL);
+
+        let signal = SIGNAL.load(Ordering::SeqCst);
+        if signal != 0 && !forwarded {
+            log::info!("Forwarding signal {} to honggfuzz", signal);
+            terminate(child, signal);
+            forwarded = true;
+        }
+
+        let Some(plateau) = supervision.plateau else {
+            continue;
+        };
+        if forwarded || last_check.elapsed() < STATS_INTERVAL {
+            continue;
+        }
+        last_check = Instant::now();
+        let last_coverage = match stats::runs_since(&supervision.stats, started) {
+            // honggfuzz starts with the time it started as the last coverage update
+            Ok(mut runs) => runs
+                .pop()
+                .map_or(started, |run| run.last_coverage_update.max(started)),
+            Err(err) => {
+                log::debug!("Failed to read the stats of honggfuzz: {:#}", err);
+                continue;
+            }
+        };
+        if Duration::from_secs(unix_time().saturating_sub(last_coverage)) >= plateau {
+            println!(
+                "no new coverage for {}, stopping honggfuzz",
+                humantime::format_duration(plateau)
+            );
+            terminate(child, libc::SIGTERM);
+            forwarded = true;
+        }
+    }
+}
+
+/// Send `signal` to `child`, honggfuzz then stops its fuzzing threads and prints its summary.
+fn terminate(child: &Child, signal: libc::c_int) {
+    // SAFETY: `kill` has no memory safety preconditions, the child was not reaped yet
+    // so its pid cannot have been reused
+    unsafe {
+        libc::kill(child.id() as libc::pid_t, signal);
+    }
+}
+
+/// Copy the lines read from `input` to `output` and to `log`.
+fn tee<W: Write + 'static>(
+    input: impl Read + Send + 'static,
+    output: fn() -> W,
+    log: Arc<Mutex<RotatingLog>>,
+) -> thread::JoinHandle<io::Result<()>> {
+    thread::spawn(move || {
+        let mut input = BufReader::new(input);
+        let mut line = Vec::new();
+        while input.read_until(b'\n', &mut line)? > 0 {
+            // the terminal may be gone, the log is still worth keeping
+            let _ = output().write_all(&line);
+            log.lock()
+                .expect("a tee thread panicked while writing")
+                .write(&line)?;
+            line.clear();
+        }
+        Ok(())
+    })
+}
+
+/// A log file which is moved aside once it grew too big, keeping a few old ones.
+struct RotatingLog {
+    dir: PathBuf,
+    file: fs::File,
+    size: u64,
+}
+
+impl RotatingLog {
+    /// Start a new log in `dir`, the log of the previous run is rotated.
+    fn create(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir)?;
+        rotate(dir)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            file: fs::File::create(dir.join(LOG_NAME))?,
+            size: 0,
+        })
+    }
+
+    fn write(&mut self, line: &[u8]) -> io::Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 > MAX_LOG_SIZE {
+            rotate(&self.dir)?;
+            self.file = fs::File::create(self.dir.join(LOG_NAME))?;
+            self.size = 0;
+        }
+        self.file.write_all(line)?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+/// Shift `honggfuzz.log` to `honggfuzz.log.1`, `honggfuzz.log.1` to `honggfuzz.log.2` and so
+/// on, dropping the oldest one.
+fn rotate(dir: &Path) -> io::Result<()> {
+    let rotated = |index: usize| dir.join(format!("{}.{}", LOG_NAME, index));
+    for index in (1..MAX_ROTATED_LOGS).rev() {
+        if rotated(index).is_file() {
+            fs::rename(rotated(index), rotated(index + 1))?;
+        }
+    }
+    let current = dir.join(LOG_NAME);
+    if current.is_file() {
+        fs::rename(current, rotated(1))?;
+    }
+    Ok(())
+}
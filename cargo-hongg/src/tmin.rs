@@ -0,0 +1,207 @@
+//! Test case reduction, shrinks a single crashing input while
+//! preserving the crash it triggers.
+
+use crate::replay::{self, Outcome};
+use crate::BuildDir;
+use anyhow::Result;
+use fs_err as fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Identity of a crash as determined by honggfuzz, encoded in the crash file name
+/// like `SIGABRT.PC.7ffff7a42428.STACK.18a2f29c6e.CODE.-6.ADDR.0.INSTR.mov____0x108(%rsp),%rax.fuzz`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CrashSignature {
+    pub(crate) signal: String,
+    pub(crate) stack: String,
+}
+
+impl CrashSignature {
+    pub(crate) fn from_file_name(name: &str) -> Option<Self> {
+        let mut parts = name.split('.');
+        let signal = parts.next().filter(|signal| signal.starts_with("SIG"))?;
+        let stack = parts.skip_while(|&part| part != "STACK").nth(1)?;
+        Some(Self {
+            signal: signal.to_owned(),
+            stack: stack.to_owned(),
+        })
+    }
+}
+
+/// Shrink the crash in `crash_file` by replaying candidates through the `fuzzing_debug`
+/// build of `binary`, a candidate is kept if it panics at the same location.
+pub(crate) fn tmin_debug(
+    build_dir: &BuildDir,
+    binary: &str,
+    crash_file: &Path,
+    scratch: &Path,
+) -> Result<PathBuf> {
+    let data = fs::read(crash_file)?;
+    let location = match replay::replay(build_dir, binary, crash_file)? {
+        Outcome::Panic { location, message } => {
+            log::info!(
+                "Crash panics at {}: {}",
+                location.as_deref().unwrap_or("<unknown>"),
+                message
+            );
+            location
+        }
+        outcome => anyhow::bail!(
+            "\"{}\" does not reproduce a panic, replay ended with {:?}",
+            crash_file.display(),
+            outcome
+        ),
+    };
+
+    fs::create_dir_all(scratch)?;
+    let candidate = scratch.join("candidate");
+    let minimized = reduce(data, |input| {
+        fs::write(&candidate, input)?;
+        Ok(matches!(
+            replay::replay(build_dir, binary, &candidate)?,
+            Outcome::Panic { location: ref l, .. } if *l == location
+        ))
+    })?;
+
+    write_minimized(crash_file, &minimized)
+}
+
+/// Shrink the crash in `crash_file` by running candidates through honggfuzz with the
+/// instrumented build of `binary`, a candidate is kept if honggfuzz reports the same
+/// signal and stack hash as encoded in the original crash file name.
+pub(crate) fn tmin_release(
+    build_dir: &BuildDir,
+    binary: &str,
+    crash_file: &Path,
+    scratch: &Path,
+) -> Result<PathBuf> {
+    let data = fs::read(crash_file)?;
+    let signature = crash_file
+        .file_name()
+        .and_then(|name| CrashSignature::from_file_name(&name.to_string_lossy()))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "\"{}\" is not named like a honggfuzz crash file, use `--debug` to compare panic locations instead",
+                crash_file.display()
+            )
+        })?;
+    log::info!(
+        "Crash signature is {} with stack hash {}",
+        signature.signal,
+        signature.stack
+    );
+
+    let minimized = reduce(data, |input| {
+        Ok(honggfuzz_signature(build_dir, binary, scratch, input)?.as_ref() == Some(&signature))
+    })?;
+
+    write_minimized(crash_file, &minimized)
+}
+
+/// Run a single input through honggfuzz and return the signature of the crash, if any.
+fn honggfuzz_signature(
+    build_dir: &BuildDir,
+    binary: &str,
+    scratch: &Path,
+    input: &[u8],
+) -> Result<Option<CrashSignature>> {
+    let input_dir = scratch.join("input");
+    let crash_dir = scratch.join("crashes");
+    for dir in [&input_dir, &crash_dir] {
+        if dir.exists() {
+            fs::remove_dir_all(dir)?;
+        }
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(input_dir.join("candidate"), input)?;
+
+    let mut cmd = Command::new(build_dir.honggfuzz());
+    cmd.envs(build_dir.sanitizer_env())
+        .arg("-W")
+        .arg(scratch)
+        .arg("--input")
+        .arg(&input_dir)
+        .arg("--crashdir")
+        .arg(&crash_dir)
+        .args(["-n", "1", "-N", "1", "-v", "--quietly", "-P"])
+        .args(build_dir.honggfuzz_args())
+        .arg("--")
+        .arg(build_dir.instrumented_binary(binary))
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // the exit code of honggfuzz does not tell anything about the input
+    let _ = cmd.status()?;
+
+    for entry in fs::read_dir(&crash_dir)? {
+        let name = entry?.file_name();
+        if let Some(signature) = CrashSignature::from_file_name(&name.to_string_lossy()) {
+            return Ok(Some(signature));
+        }
+    }
+    Ok(None)
+}
+
+/// Reduce `data` to a smaller input for which `reproduces` still holds,
+/// by removing chunks of halving size until single bytes can't be removed anymore.
+fn reduce(mut data: Vec<u8>, mut reproduces: impl FnMut(&[u8]) -> Result<bool>) -> Result<Vec<u8>> {
+    let mut chunk = std::cmp::max(data.len() / 2, 1);
+    loop {
+        let mut offset = 0;
+        while offset < data.len() {
+            let end = std::cmp::min(offset + chunk, data.len());
+            let candidate = Vec::from_iter(data[..offset].iter().chain(&data[end..]).copied());
+            if reproduces(&candidate)? {
+                log::info!("Reduced to {} bytes", candidate.len());
+                data = candidate;
+            } else {
+                offset += chunk;
+            }
+        }
+        if chunk == 1 {
+            break;
+        }
+        chunk /= 2;
+    }
+    Ok(data)
+}
+
+/// Store the minimized input next to the original crash file.
+fn write_minimized(crash_file: &Path, data: &[u8]) -> Result<PathBuf> {
+    let mut name = crash_file
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("\"{}\" is not a file", crash_file.display()))?
+        .to_owned();
+    name.push(".min");
+    let path = crash_file.with_file_name(name);
+    fs::write(&path, data)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature() {
+        assert_eq!(
+            CrashSignature::from_file_name(
+                "SIGABRT.PC.7ffff7a42428.STACK.18a2f29c6e.CODE.-6.ADDR.0.INSTR.mov____0x108(%rsp),%rax.fuzz"
+            ),
+            Some(CrashSignature {
+                signal: "SIGABRT".to_owned(),
+                stack: "18a2f29c6e".to_owned(),
+            })
+        );
+        assert_eq!(CrashSignature::from_file_name("input.fuzz"), None);
+    }
+
+    #[test]
+    fn reduce_keeps_crash() {
+        let data = b"xxxxxxhexxxxxxxxyxxxxxxxxxxxxxxxxxxxxxx".to_vec();
+        let minimized = reduce(data, |input| {
+            Ok(input.contains(&b'h') && input.contains(&b'e') && input.contains(&b'y'))
+        })
+        .unwrap();
+        assert_eq!(minimized, b"hey");
+    }
+}
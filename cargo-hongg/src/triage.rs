@@ -0,0 +1,219 @@
+//! Crash triage, replays crashes through the `fuzzing_debug` build and groups
+//! them by the frames of their backtrace which belong to the fuzzed code.
+
+use crate::replay::{self, Outcome};
+use crate::BuildDir;
+use anyhow::Result;
+use fs_err as fs;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Frames of the standard library, the panic machinery and `hongg` itself
+/// don't tell anything about the bug.
+const IGNORED_FRAME_PREFIXES: &[&str] = &[
+    "std::",
+    "core::",
+    "alloc::",
+    "hongg::",
+    "__rust",
+    "rust_begin_unwind",
+    "rust_panic",
+];
+
+/// Number of top frames crashes have to share to be grouped, unless given otherwise.
+pub(crate) const DEFAULT_FRAMES: usize = 3;
+
+/// A frame of a Rust backtrace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Frame {
+    /// Demangled name of the function, i.e. `parser::parse_header`.
+    pub(crate) function: String,
+    /// Source location, i.e. `./src/lib.rs:10:5`.
+    pub(crate) location: Option<String>,
+}
+
+/// Crashes which share the same top frames.
+#[derive(Debug, Clone)]
+pub(crate) struct Bucket {
+    /// The top frames of the fuzzed code, empty if the crash has no backtrace.
+    pub(crate) frames: Vec<Frame>,
+    /// Outcome of the representative.
+    pub(crate) outcome: Outcome,
+    /// All crashing inputs, the smallest one first which represents the bucket.
+    pub(crate) inputs: Vec<PathBuf>,
+}
+
+impl Bucket {
+    pub(crate) fn representative(&self) -> &PathBuf {
+        &self.inputs[0]
+    }
+}
+
+/// Result of triaging a set of crashes.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Triage {
+    /// Buckets with the most crashes first.
+    pub(crate) buckets: Vec<Bucket>,
+    /// Crashes which did not crash the debug build.
+    pub(crate) not_reproduced: Vec<PathBuf>,
+}
+
+/// Replay all `crashes` through the `fuzzing_debug` build of `binary` and group them
+/// by their top `frames` frames which are not part of std or `hongg`.
+pub(crate) fn triage(
+    build_dir: &BuildDir,
+    binary: &str,
+    crashes: &[PathBuf],
+    frames: usize,
+) -> Result<Triage> {
+    let mut triage = Triage::default();
+    // crashes without a backtrace are grouped by the way they ended
+    let mut buckets = BTreeMap::<(Vec<String>, String), Bucket>::new();
+    for crash in crashes {
+        let (outcome, stderr) = replay::replay_with_backtrace(build_dir, binary, crash)?;
+        log::debug!("{}: {}", crash.display(), outcome);
+        let kind = match &outcome {
+            Outcome::Pass => {
+                triage.not_reproduced.push(crash.clone());
+                continue;
+            }
+            Outcome::Panic { .. } => "panic".to_owned(),
+            Outcome::Signal(_) | Outcome::Exit(_) => outcome.to_string(),
+        };
+        let top = Vec::from_iter(parse_backtrace(&stderr).into_iter().take(frames));
+        let key = (
+            Vec::from_iter(top.iter().map(|frame| frame.function.clone())),
+            kind,
+        );
+        buckets
+            .entry(key)
+            .or_insert_with(|| Bucket {
+                frames: top,
+                outcome,
+                inputs: Vec::new(),
+            })
+            .inputs
+            .push(crash.clone());
+    }
+
+    for mut bucket in buckets.into_values() {
+        let mut sized = Vec::with_capacity(bucket.inputs.len());
+        for input in bucket.inputs {
+            sized.push((fs::metadata(&input)?.len(), input));
+        }
+        sized.sort();
+        bucket.inputs = Vec::from_iter(sized.into_iter().map(|(_, input)| input));
+        triage.buckets.push(bucket);
+    }
+    triage
+        .buckets
+        .sort_by_key(|bucket| std::cmp::Reverse(bucket.inputs.len()));
+    Ok(triage)
+}
+
+/// Extract the frames of the fuzzed code from the backtrace printed by the default panic hook.
+pub(crate) fn parse_backtrace(stderr: &str) -> Vec<Frame> {
+    let mut frames = Vec::<Frame>::new();
+    for line in stderr.lines() {
+        let line = line.trim();
+        if let Some(location) = line.strip_prefix("at ") {
+            if let Some(frame) = frames.last_mut() {
+                frame.location.get_or_insert_with(|| location.to_owned());
+            }
+            continue;
+        }
+        let function = match line.split_once(": ") {
+            Some((index, function)) if index.chars().all(|c| c.is_ascii_digit()) => function,
+            _ => continue,
+        };
+        frames.push(Frame {
+            function: function.to_owned(),
+            location: None,
+        });
+    }
+    frames.retain(|frame| {
+        let function = frame.function.trim_start_matches('<');
+        !IGNORED_FRAME_PREFIXES
+            .iter()
+            .any(|prefix| function.starts_with(prefix))
+    });
+    frames
+}
+
+/// Print one line per bucket with the representative crash and the frames it was grouped by.
+pub(crate) fn print(triage: &Triage) {
+    for (index, bucket) in triage.buckets.iter().enumerate() {
+        println!(
+            "#{} {} crashes: {}",
+            index + 1,
+            bucket.inputs.len(),
+            bucket.outcome
+        );
+        for frame in &bucket.frames {
+            println!(
+                "    at {} ({})",
+                frame.function,
+                frame.location.as_deref().unwrap_or("<unknown>")
+            );
+        }
+        println!("    representative: {}", bucket.representative().display());
+    }
+    for input in &triage.not_reproduced {
+        println!("not reproduced: {}", input.display());
+    }
+    println!(
+        "{} unique crashes in {} crash files",
+        triage.buckets.len(),
+        triage
+            .buckets
+            .iter()
+            .map(|bucket| bucket.inputs.len())
+            .sum::<usize>()
+            + triage.not_reproduced.len()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backtrace() {
+        let stderr = "\
+thread 'main' (11898) panicked at src/lib.rs:10:5:
+index out of bounds: the len is 3 but the index is 7
+stack backtrace:
+   0: __rustc::rust_begin_unwind
+             at /rustc/59807616e1fa2540724bfbac14d7976d7e4a3860/library/std/src/panicking.rs:689:5
+   1: core::panicking::panic_bounds_check
+             at /rustc/59807616e1fa2540724bfbac14d7976d7e4a3860/library/core/src/panicking.rs:280:5
+   2: parser::parse_header
+             at ./src/lib.rs:10:5
+   3: <parser::Parser as core::str::FromStr>::from_str
+             at ./src/lib.rs:42:9
+   4: parse::main::{{closure}}
+             at ./src/main.rs:5:9
+   5: hongg::fuzz
+             at /root/crate/hongg/src/lib.rs:326:5
+   6: <core::ops::function::FnOnce>::call_once
+note: Some details are omitted, run with `RUST_BACKTRACE=full` for a verbose backtrace.
+";
+        assert_eq!(
+            parse_backtrace(stderr),
+            vec![
+                Frame {
+                    function: "parser::parse_header".to_owned(),
+                    location: Some("./src/lib.rs:10:5".to_owned()),
+                },
+                Frame {
+                    function: "<parser::Parser as core::str::FromStr>::from_str".to_owned(),
+                    location: Some("./src/lib.rs:42:9".to_owned()),
+                },
+                Frame {
+                    function: "parse::main::{{closure}}".to_owned(),
+                    location: Some("./src/main.rs:5:9".to_owned()),
+                },
+            ]
+        );
+    }
+}
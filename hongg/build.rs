@@ -17,7 +17,7 @@ fn run_cmd(cmd: &mut Command) -> anyhow::Result<()> {
     .join(" ");
     let status = cmd
         .status()
-        .expect(format!("Failed to spawn process \"{full}\"").as_str());
+        .unwrap_or_else(|_| panic!("Failed to spawn process \"{full}\""));
 
     if !status.success() {
         anyhow::bail!("Command failed ({:?}): \"{}\"", &status, &full);
@@ -25,8 +25,45 @@ fn run_cmd(cmd: &mut Command) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// C compiler and flags to build the hfuzz static libraries for `target`.
+///
+/// Respects `CC_<target>` and `CFLAGS_<target>` like the `cc` crate does,
+/// otherwise falls back to `musl-gcc` for musl targets and `cc -m32` for 32-bit x86.
+fn target_cc(target: &str) -> (String, String) {
+    let target_var = |name: &str| {
+        env::var(format!("{}_{}", name, target))
+            .or_else(|_| env::var(format!("{}_{}", name, target.replace('-', "_"))))
+            .ok()
+    };
+    println!(
+        "cargo::rerun-if-env-changed=CC_{}",
+        target.replace('-', "_")
+    );
+    println!(
+        "cargo::rerun-if-env-changed=CFLAGS_{}",
+        target.replace('-', "_")
+    );
+
+    let cc = target_var("CC").unwrap_or_else(|| {
+        if target.ends_with("-musl") {
+            "musl-gcc".to_owned()
+        } else {
+            "cc".to_owned()
+        }
+    });
+    let cflags = target_var("CFLAGS").unwrap_or_else(|| {
+        if target.starts_with("i686-") || target.starts_with("i586-") {
+            "-O3 -m32".to_owned()
+        } else {
+            "-O3".to_owned()
+        }
+    });
+    (cc, cflags)
+}
+
 fn main() -> anyhow::Result<()> {
-    let mut make = make_cmd::gnu_make();
+    println!("cargo::rustc-check-cfg=cfg(fuzzing)");
+    println!("cargo::rustc-check-cfg=cfg(fuzzing_debug)");
 
     // Only build honggfuzz binaries if we are in the process of building an instrumentized binary
     let honggfuzz_target = match env::var("CARGO_HONGGFUZZ_TARGET_DIR") {
@@ -61,23 +98,52 @@ fn main() -> anyhow::Result<()> {
     let manifest_dir = manifest_dir.canonicalize()?;
     let manifest_dir = manifest_dir.as_path();
 
+    let host = env::var("HOST")?;
+    let target = env::var("TARGET")?;
+
+    use fs_err as fs;
+
     // clean upsteam honggfuzz directory
+    // TODO: maybe it's not a good idea to always clean the sources..
     run_cmd(
-        make.args("-C honggfuzz clean".split_ascii_whitespace())
+        make_cmd::gnu_make()
+            .args("-C honggfuzz clean".split_ascii_whitespace())
             .current_dir(manifest_dir),
     )?;
-    // TODO: maybe it's not a good idea to always clean the sources..
 
     // build honggfuzz command and hfuzz static library
     run_cmd(
-        make.args(
-            "-C honggfuzz honggfuzz libhfuzz/libhfuzz.a libhfcommon/libhfcommon.a"
-                .split_ascii_whitespace(),
-        )
-        .current_dir(manifest_dir),
+        make_cmd::gnu_make()
+            .args(
+                "-C honggfuzz honggfuzz libhfuzz/libhfuzz.a libhfcommon/libhfcommon.a"
+                    .split_ascii_whitespace(),
+            )
+            .current_dir(manifest_dir),
     )?;
 
-    use fs_err as fs;
+    // copy honggfuzz executable to honggfuzz target directory,
+    // it always runs on the host, even when fuzzing a cross compiled binary
+    fs::copy("honggfuzz/honggfuzz", honggfuzz_target.join("honggfuzz"))?;
+
+    if target != host {
+        // rebuild only the static libraries with the C compiler of the target
+        let (cc, cflags) = target_cc(&target);
+        run_cmd(
+            make_cmd::gnu_make()
+                .args("-C honggfuzz clean".split_ascii_whitespace())
+                .current_dir(manifest_dir),
+        )?;
+        run_cmd(
+            make_cmd::gnu_make()
+                .args(
+                    "-C honggfuzz libhfuzz/libhfuzz.a libhfcommon/libhfcommon.a"
+                        .split_ascii_whitespace(),
+                )
+                .env("CC", cc)
+                .env("CFLAGS", cflags)
+                .current_dir(manifest_dir),
+        )?;
+    }
 
     fs::copy("honggfuzz/libhfuzz/libhfuzz.a", out_dir.join("libhfuzz.a"))?;
     fs::copy(
@@ -85,12 +151,9 @@ fn main() -> anyhow::Result<()> {
         out_dir.join("libhfcommon.a"),
     )?;
 
-    // copy honggfuzz executable to honggfuzz target directory
-    fs::copy("honggfuzz/honggfuzz", honggfuzz_target.join("honggfuzz"))?;
-
     // tell cargo how to link final executable to hfuzz static library
-    println!("cargo:rustc-link-lib=static={}", "hfuzz");
-    println!("cargo:rustc-link-lib=static={}", "hfcommon");
+    println!("cargo:rustc-link-lib=static=hfuzz");
+    println!("cargo:rustc-link-lib=static=hfcommon");
     println!("cargo:rustc-link-search=native={}", out_dir.display());
     Ok(())
 }
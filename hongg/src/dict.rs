@@ -0,0 +1,69 @@
+//! Dictionary tokens embedded in the fuzzing target with [`dict!`](crate::dict), for
+//! `cargo hongg dict` to extract them from the built binary.
+//!
+//! Every invocation of `dict!` places a static in the `hongg_dict` linker section, made of one
+//! record per token: its length as little-endian `u16` followed by its bytes.
+
+/// Name of the linker section holding the tokens.
+pub const SECTION: &str = "hongg_dict";
+
+/// A token given to [`dict!`](crate::dict), either a string or a byte string.
+#[doc(hidden)]
+pub struct Token<T>(pub T);
+
+impl<'a> Token<&'a str> {
+    pub const fn bytes(&self) -> &'a [u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl<'a> Token<&'a [u8]> {
+    pub const fn bytes(&self) -> &'a [u8] {
+        self.0
+    }
+}
+
+impl<'a, const N: usize> Token<&'a [u8; N]> {
+    pub const fn bytes(&self) -> &'a [u8] {
+        self.0
+    }
+}
+
+/// Size of the records of all `tokens`.
+#[doc(hidden)]
+pub const fn encoded_len(tokens: &[&[u8]]) -> usize {
+    let mut len = 0;
+    let mut i = 0;
+    while i < tokens.len() {
+        assert!(
+            !tokens[i].is_empty() && tokens[i].len() <= u16::MAX as usize,
+            "dictionary tokens must not be empty nor longer than 65535 bytes"
+        );
+        len += 2 + tokens[i].len();
+        i += 1;
+    }
+    len
+}
+
+/// The records of all `tokens`, `N` has to be their `encoded_len`.
+#[doc(hidden)]
+pub const fn encode<const N: usize>(tokens: &[&[u8]]) -> [u8; N] {
+    let mut records = [0; N];
+    let mut offset = 0;
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+        let len = (token.len() as u16).to_le_bytes();
+        records[offset] = len[0];
+        records[offset + 1] = len[1];
+        offset += 2;
+        let mut j = 0;
+        while j < token.len() {
+            records[offset + j] = token[j];
+            j += 1;
+        }
+        offset += token.len();
+        i += 1;
+    }
+    records
+}
@@ -0,0 +1,125 @@
+//! Drive the futures of [`fuzz_async!`](crate::fuzz_async) to completion.
+//!
+//! By default a minimal executor polls the future on the current thread and parks it
+//! while the future is pending. It only supports futures which are woken by other threads
+//! or by themselves, such as plain `async fn` parsers and codecs working on in-memory data.
+//!
+//! With the `tokio` feature, a current-thread runtime with all drivers enabled is built once
+//! per thread and reused by all iterations, so `tokio::spawn`, timers and I/O work too.
+//! Note that tokio catches panics of spawned tasks, while panics of the future
+//! itself propagate like with [`fuzz`](crate::fuzz()).
+
+use std::future::Future;
+
+/// Run `future` to completion on the current thread and return its output.
+///
+/// ```rust
+/// assert_eq!(hongg::executor::block_on(async { 6 * 7 }), 42);
+/// ```
+#[cfg(not(feature = "tokio"))]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::thread::{self, Thread};
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let mut future = std::pin::pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+thread_local! {
+    static RUNTIME: tokio::runtime::Runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to build the tokio runtime");
+}
+
+/// Run `future` to completion on the current-thread runtime and return its output.
+///
+/// ```rust
+/// assert_eq!(hongg::executor::block_on(async { 6 * 7 }), 42);
+/// ```
+#[cfg(feature = "tokio")]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    RUNTIME.with(|runtime| runtime.block_on(future))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    /// Pending for the first `polls` polls, woken by another thread every time.
+    struct WokenLater {
+        polls: usize,
+    }
+
+    impl Future for WokenLater {
+        type Output = &'static str;
+
+        fn poll(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Self::Output> {
+            if self.polls == 0 {
+                return Poll::Ready("done");
+            }
+            self.polls -= 1;
+            let waker = context.waker().clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(10));
+                waker.wake();
+            });
+            Poll::Pending
+        }
+    }
+
+    #[test]
+    fn pending_before_wake() {
+        assert_eq!(block_on(WokenLater { polls: 3 }), "done");
+
+        let mut yielded = false;
+        let yield_once = std::future::poll_fn(|context| {
+            if yielded {
+                Poll::Ready(42)
+            } else {
+                yielded = true;
+                context.waker().wake_by_ref();
+                Poll::Pending
+            }
+        });
+        assert_eq!(block_on(yield_once), 42);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn runtime_reused() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        // a task spawned in one iteration only runs on if the runtime is still the same
+        let ran = Arc::new(AtomicBool::new(false));
+        let spawned = Arc::clone(&ran);
+        block_on(async move {
+            tokio::spawn(async move { spawned.store(true, Ordering::SeqCst) });
+        });
+        assert!(!ran.load(Ordering::SeqCst));
+        block_on(async {
+            tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+        });
+        assert!(ran.load(Ordering::SeqCst));
+    }
+}
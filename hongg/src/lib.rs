@@ -208,6 +208,11 @@
 /// Re-export of arbitrary crate used to generate structured inputs
 pub use arbitrary;
 
+pub mod dict;
+pub mod executor;
+pub mod mutator;
+pub mod replay;
+
 #[cfg(all(fuzzing, not(fuzzing_debug)))]
 extern "C" {
     fn HF_ITER(buf_ptr: *mut *const u8, len_ptr: *mut usize);
@@ -330,6 +335,88 @@ where
     std::process::exit(2);
 }
 
+/// Fuzz a closure by passing it `state` along with the `&[u8]`, then run `reset` on the state.
+///
+/// Use it when the harness has to mutate some state, like a parser reusing its buffers. The
+/// result of an iteration must only depend on its input, so `reset` has to bring the state back
+/// to what it was before the first iteration. See [`fuzz_with_checked_state`] to verify it.
+///
+/// ```rust,should_panic
+/// # use hongg::fuzz_with_state;
+/// # fn main() {
+/// let mut seen = Vec::new();
+/// loop {
+///     fuzz_with_state(
+///         &mut seen,
+///         |seen, data| {
+///             seen.extend_from_slice(data);
+///             if seen.as_slice() == b"hey" {
+///                 panic!("BOOM")
+///             }
+///         },
+///         |seen| seen.clear(),
+///     );
+/// }
+/// # }
+/// ```
+pub fn fuzz_with_state<S, F, R>(state: &mut S, closure: F, reset: R)
+where
+    F: FnOnce(&mut S, &[u8]),
+    R: FnOnce(&mut S),
+{
+    // the reset is part of the iteration, the debug build exits right after it
+    fuzz(|data| {
+        closure(state, data);
+        reset(state);
+    });
+}
+
+/// Like [`fuzz_with_state`], but the `fuzzing_debug` build, as used by `cargo hongg debug` and
+/// `cargo hongg replay`, panics if the state after `reset` differs from the state before the
+/// iteration. This catches harnesses whose results depend on earlier inputs.
+///
+/// ```rust,should_panic
+/// # use hongg::fuzz_with_checked_state;
+/// # fn main() {
+/// let mut seen = Vec::new();
+/// loop {
+///     fuzz_with_checked_state(
+///         &mut seen,
+///         |seen, data| seen.extend_from_slice(data),
+///         |seen| seen.clear(),
+///     );
+/// }
+/// # }
+/// ```
+pub fn fuzz_with_checked_state<S, F, R>(state: &mut S, closure: F, reset: R)
+where
+    S: Clone + PartialEq + std::fmt::Debug,
+    F: FnOnce(&mut S, &[u8]),
+    R: FnOnce(&mut S),
+{
+    if cfg!(fuzzing_debug) {
+        fuzz(|data| checked_iteration(state, data, closure, reset));
+    } else {
+        fuzz_with_state(state, closure, reset);
+    }
+}
+
+/// A single iteration of [`fuzz_with_checked_state`] in the `fuzzing_debug` build.
+fn checked_iteration<S, F, R>(state: &mut S, data: &[u8], closure: F, reset: R)
+where
+    S: Clone + PartialEq + std::fmt::Debug,
+    F: FnOnce(&mut S, &[u8]),
+    R: FnOnce(&mut S),
+{
+    let initial = state.clone();
+    closure(state, data);
+    reset(state);
+    assert_eq!(
+        *state, initial,
+        "the state after the reset differs from the state before the iteration"
+    );
+}
+
 /// Fuzz a closure-like block of code by passing it an object of arbitrary type.
 ///
 /// You can choose the type of the argument using the syntax as in the example below.
@@ -353,17 +440,157 @@ where
 /// }
 /// # }
 /// ```
-
+///
+/// State mutated by the harness is given with `state:` and the closure which resets it after every
+/// iteration with `reset:`, see [`fuzz_with_state`]. With `checked_state:` instead, the
+/// `fuzzing_debug` build checks the reset, see [`fuzz_with_checked_state`].
+///
+/// ```rust,should_panic
+/// # use hongg::fuzz;
+/// # fn main() {
+/// let mut stack = Vec::new();
+/// loop {
+///     fuzz!(checked_state: &mut stack, reset: |stack| stack.clear(), |stack, ops: Vec<u8>| {
+///         for op in ops {
+///             match op {
+///                 0 => drop(stack.pop()),
+///                 op => stack.push(op),
+///             }
+///         }
+///     });
+/// }
+/// # }
+/// ```
 #[macro_export]
 macro_rules! fuzz {
+    (state: $state:expr, reset: $reset:expr, |$s:ident, $($harness:tt)*) => {
+        $crate::fuzz_with_state(
+            $state,
+            |$s, data| ($crate::__harness!(|$($harness)*))(data),
+            $reset,
+        );
+    };
+    (checked_state: $state:expr, reset: $reset:expr, |$s:ident, $($harness:tt)*) => {
+        $crate::fuzz_with_checked_state(
+            $state,
+            |$s, data| ($crate::__harness!(|$($harness)*))(data),
+            $reset,
+        );
+    };
+    ($($harness:tt)*) => {
+        $crate::fuzz($crate::__harness!($($harness)*));
+    };
+}
+
+/// Define the `main` function of a fuzzing target, which calls [`fuzz!`] with the given
+/// closure-like block of code in the persistent fuzzing loop.
+///
+/// An optional `init` expression is evaluated once before the loop, a reference to its value is
+/// given to every iteration as context, which is the place for expensive setup.
+///
+/// ```rust,no_run
+/// hongg::fuzz_target!(|data: &[u8]| {
+///     if data == b"hey" {
+///         panic!("BOOM")
+///     }
+/// });
+/// ```
+///
+/// ```rust,no_run
+/// # struct Grammar;
+/// # impl Grammar {
+/// #     fn load() -> Self { Grammar }
+/// #     fn accepts(&self, _: &str) -> bool { true }
+/// # }
+/// hongg::fuzz_target!(init: Grammar::load(), |grammar, text: &str| {
+///     grammar.accepts(text);
+/// });
+/// ```
+#[macro_export]
+macro_rules! fuzz_target {
+    (init: $init:expr, |$context:ident, $($harness:tt)*) => {
+        fn main() {
+            let $context = &$init;
+            loop {
+                $crate::fuzz!(|$($harness)*);
+            }
+        }
+    };
+    (init: $init:expr, |$($harness:tt)*) => {
+        fn main() {
+            // kept alive for the whole loop, like a context which is given to the iterations
+            let _context = $init;
+            loop {
+                $crate::fuzz!(|$($harness)*);
+            }
+        }
+    };
+    (|$($harness:tt)*) => {
+        fn main() {
+            loop {
+                $crate::fuzz!(|$($harness)*);
+            }
+        }
+    };
+}
+
+/// Fuzz an async closure-like block of code, like [`fuzz!`] but with a body which evaluates to
+/// a future. The future is driven to completion in every iteration by [`executor::block_on`],
+/// panics abort the process like with [`fuzz!`].
+///
+/// With the `tokio` feature, a current-thread runtime built once is reused across iterations.
+///
+/// ```rust,should_panic
+/// # use hongg::fuzz_async;
+/// async fn parse(data: &[u8]) -> Result<u32, ()> {
+///     match data {
+///         [a, b, c, d] => Ok(u32::from_le_bytes([*a, *b, *c, *d])),
+///         _ => Err(()),
+///     }
+/// }
+///
+/// # fn main() {
+/// loop {
+///     fuzz_async!(|data: &[u8]| async {
+///         if parse(data).await == Ok(0xdead_beef) {
+///             panic!("BOOM")
+///         }
+///     });
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! fuzz_async {
+    (|$buf:ident| $body:expr) => {
+        $crate::fuzz!(|$buf| {
+            $crate::executor::block_on($body);
+        });
+    };
+    (|$buf:ident: &[u8]| $body:expr) => {
+        $crate::fuzz!(|$buf: &[u8]| {
+            $crate::executor::block_on($body);
+        });
+    };
+    (|$buf:ident: $dty:ty| $body:expr) => {
+        $crate::fuzz!(|$buf: $dty| {
+            $crate::executor::block_on($body);
+        });
+    };
+}
+
+/// Turn the closure-like block of [`fuzz!`] into a closure taking a `&[u8]`,
+/// which decodes the argument with `arbitrary` unless it is a `&[u8]` itself.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __harness {
     (|$buf:ident| $body:block) => {
-        $crate::fuzz(|$buf| $body);
+        |$buf: &[u8]| $body
     };
     (|$buf:ident: &[u8]| $body:block) => {
-        $crate::fuzz(|$buf| $body);
+        |$buf: &[u8]| $body
     };
     (|$buf:ident: $dty:ty| $body:block) => {
-        $crate::fuzz(|$buf| {
+        |$buf: &[u8]| {
             let $buf: $dty = {
                 use $crate::arbitrary::{Arbitrary, Unstructured};
 
@@ -376,6 +603,116 @@ macro_rules! fuzz {
             };
 
             $body
-        });
+        }
+    };
+}
+
+/// Replay a single input file through a closure-like block of code, like [`fuzz!`] would do
+/// with an input generated by honggfuzz, see [`replay::run_file`].
+///
+/// ```rust
+/// # let path = std::env::temp_dir().join("hongg-replay-file-doc");
+/// # std::fs::write(&path, b"hex").unwrap();
+/// hongg::replay_file!(&path, |data: &[u8]| {
+///     assert_ne!(data, b"hey");
+/// });
+/// ```
+#[macro_export]
+macro_rules! replay_file {
+    ($path:expr, $($harness:tt)*) => {
+        $crate::replay::run_file($path, $crate::__harness!($($harness)*))
+    };
+}
+
+/// Replay every input file of a directory through a closure-like block of code, like [`fuzz!`]
+/// would do with an input generated by honggfuzz, see [`replay::run_dir`].
+///
+/// ```rust
+/// # let dir = std::env::temp_dir().join("hongg-replay-dir-doc");
+/// # std::fs::create_dir_all(&dir).unwrap();
+/// # std::fs::write(dir.join("input"), b"\x01\x02").unwrap();
+/// hongg::replay_dir!(&dir, |pair: (u8, u8)| {
+///     assert!(pair.0.checked_add(pair.1).is_some());
+/// });
+/// ```
+#[macro_export]
+macro_rules! replay_dir {
+    ($dir:expr, $($harness:tt)*) => {
+        $crate::replay::run_dir($dir, $crate::__harness!($($harness)*))
+    };
+}
+
+/// Define the `main` function of a mutator binary, which mutates the input honggfuzz
+/// hands over with the given [`Mutator`](mutator::Mutator), see [`mutator`].
+///
+/// ```rust,no_run
+/// hongg::mutator_main!(|data: &mut Vec<u8>, seed: u64| {
+///     data.extend_from_slice(&seed.to_le_bytes());
+/// });
+/// ```
+#[macro_export]
+macro_rules! mutator_main {
+    ($mutator:expr) => {
+        fn main() {
+            $crate::mutator::main($mutator)
+        }
+    };
+}
+
+/// Embed dictionary tokens into the fuzzing target, strings or byte strings which honggfuzz
+/// inserts into the inputs it generates. Run `cargo hongg dict --bin <target>` to extract them
+/// from the binary into a dictionary file which `cargo hongg fuzz` picks up, with `--strings`
+/// the printable string literals of the binary are added too.
+///
+/// It can be used any number of times, as an item or a statement.
+///
+/// ```rust
+/// hongg::dict!["GET ", "POST ", "HTTP/1.1", "\r\n", b"\xff\xfe"];
+/// ```
+#[macro_export]
+macro_rules! dict {
+    ($($token:expr),* $(,)?) => {
+        const _: () = {
+            const TOKENS: &[&[u8]] = &[$($crate::dict::Token($token).bytes()),*];
+            #[used]
+            #[cfg_attr(target_vendor = "apple", link_section = "__DATA,hongg_dict")]
+            #[cfg_attr(not(target_vendor = "apple"), link_section = "hongg_dict")]
+            static DICT: [u8; $crate::dict::encoded_len(TOKENS)] =
+                $crate::dict::encode(TOKENS);
+        };
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checked_state_reset() {
+        let mut seen = vec![1];
+        checked_iteration(
+            &mut seen,
+            b"hey",
+            |seen, data| seen.extend_from_slice(data),
+            |seen| seen.truncate(1),
+        );
+        assert_eq!(seen, vec![1]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "the state after the reset differs from the state before the iteration"
+    )]
+    fn checked_state_not_reset() {
+        let mut seen = Vec::new();
+        checked_iteration(
+            &mut seen,
+            b"hey",
+            |seen, data| seen.extend_from_slice(data),
+            // only undoes the last byte of the input
+            |seen| {
+                seen.pop();
+            },
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! Custom mutators, to generate structurally valid inputs for formats the generic
+//! mutations of honggfuzz hardly ever get right.
+//!
+//! A mutator is a separate binary of the package, honggfuzz runs it through its external
+//! mutation command (`--mutate_cmd`) instead of its internal mutators. It is given the path of
+//! a file which it has to fill with the next input. As honggfuzz hands over an empty file, an
+//! input of the corpus is picked as starting point of the mutation, `cargo hongg fuzz --mutator`
+//! tells the mutator where the corpus is.
+//!
+//! ```rust,no_run
+//! // src/bin/mutator.rs
+//! struct FlipBits;
+//!
+//! impl hongg::mutator::Mutator for FlipBits {
+//!     fn mutate(&mut self, data: &mut Vec<u8>, seed: u64) {
+//!         if data.is_empty() {
+//!             data.push(seed as u8);
+//!         } else {
+//!             let index = seed as usize % data.len();
+//!             data[index] ^= 1 << (seed >> 61);
+//!         }
+//!     }
+//! }
+//!
+//! hongg::mutator_main!(FlipBits);
+//! ```
+//!
+//! ```sh
+//! cargo hongg fuzz --bin example --mutator mutator
+//! ```
+
+use std::collections::hash_map::RandomState;
+use std::env;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The environment variable naming the corpus directory to pick inputs to mutate from.
+pub const CORPUS_ENV: &str = "HFUZZ_MUTATOR_CORPUS";
+
+/// A structure-aware mutation of fuzzing inputs.
+pub trait Mutator {
+    /// Mutate `data` in place, `data` is empty if there is no corpus to start from.
+    ///
+    /// All random decisions should be derived from `seed`, i.e. with
+    /// `rand::SeedableRng::seed_from_u64`, so a mutation can be reproduced.
+    fn mutate(&mut self, data: &mut Vec<u8>, seed: u64);
+}
+
+impl<F> Mutator for F
+where
+    F: FnMut(&mut Vec<u8>, u64),
+{
+    fn mutate(&mut self, data: &mut Vec<u8>, seed: u64) {
+        self(data, seed)
+    }
+}
+
+/// Run `mutator` with the command line given by honggfuzz, see [`mutator_main!`](crate::mutator_main).
+///
+/// Exits with an error if the file to mutate cannot be read or written.
+pub fn main<M: Mutator>(mut mutator: M) {
+    let corpus = env::var_os(CORPUS_ENV);
+    let result = match env::args_os().nth(1) {
+        Some(path) => mutate_file(
+            &mut mutator,
+            Path::new(&path),
+            corpus.as_deref().map(Path::new),
+            random_seed(),
+        ),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "missing the path of the file to mutate",
+        )),
+    };
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+/// Mutate the file at `path` in place, or an input of the `corpus` if the file is empty.
+fn mutate_file<M: Mutator>(
+    mutator: &mut M,
+    path: &Path,
+    corpus: Option<&Path>,
+    seed: u64,
+) -> io::Result<()> {
+    let mut data = fs::read(path)?;
+    if data.is_empty() {
+        if let Some(corpus) = corpus {
+            data = pick_input(corpus, seed)?;
+        }
+    }
+    mutator.mutate(&mut data, seed);
+    fs::write(path, data)
+}
+
+/// The content of one of the files directly within `corpus`, chosen by `seed`.
+fn pick_input(corpus: &Path, seed: u64) -> io::Result<Vec<u8>> {
+    let mut inputs = Vec::new();
+    for entry in fs::read_dir(corpus)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() && !entry.file_name().to_string_lossy().starts_with('.') {
+            inputs.push(entry.path());
+        }
+    }
+    if inputs.is_empty() {
+        return Ok(Vec::new());
+    }
+    inputs.sort();
+    // the low bits are left to the mutator
+    fs::read(&inputs[(seed >> 32) as usize % inputs.len()])
+}
+
+/// A new seed for every run of the mutator, `RandomState` is randomly keyed per process.
+fn random_seed() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    if let Ok(now) = SystemTime::now().duration_since(UNIX_EPOCH) {
+        hasher.write_u128(now.as_nanos());
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("hongg-mutator-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn pick() {
+        let corpus = scratch("pick");
+        assert_eq!(pick_input(&corpus, 0).unwrap(), b"");
+
+        fs::write(corpus.join("a"), b"first").unwrap();
+        fs::write(corpus.join("b"), b"second").unwrap();
+        fs::write(corpus.join(".hidden"), b"hidden").unwrap();
+        fs::create_dir(corpus.join("c")).unwrap();
+        assert_eq!(pick_input(&corpus, 0).unwrap(), b"first");
+        assert_eq!(pick_input(&corpus, 1 << 32).unwrap(), b"second");
+        assert_eq!(pick_input(&corpus, (2 << 32) | 1).unwrap(), b"first");
+        fs::remove_dir_all(&corpus).unwrap();
+    }
+
+    #[test]
+    fn mutate() {
+        let dir = scratch("mutate");
+        let corpus = dir.join("input");
+        fs::create_dir(&corpus).unwrap();
+        fs::write(corpus.join("a"), b"corpus").unwrap();
+        let mut append_seed = |data: &mut Vec<u8>, seed: u64| data.push(seed as u8);
+
+        // honggfuzz hands over an empty file, the input is picked from the corpus
+        let file = dir.join("mutated");
+        fs::write(&file, b"").unwrap();
+        mutate_file(&mut append_seed, &file, Some(&corpus), u64::from(b'!')).unwrap();
+        assert_eq!(fs::read(&file).unwrap(), b"corpus!");
+
+        // a file with content is mutated in place
+        mutate_file(&mut append_seed, &file, Some(&corpus), u64::from(b'?')).unwrap();
+        assert_eq!(fs::read(&file).unwrap(), b"corpus!?");
+
+        // without a corpus the mutator starts from an empty input
+        let file = dir.join("empty");
+        fs::write(&file, b"").unwrap();
+        mutate_file(&mut append_seed, &file, None, u64::from(b'x')).unwrap();
+        assert_eq!(fs::read(&file).unwrap(), b"x");
+
+        assert!(mutate_file(&mut append_seed, &dir.join("missing"), None, 0).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
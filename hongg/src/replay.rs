@@ -0,0 +1,129 @@
+//! Run a fuzzing harness on fixed inputs outside of fuzzing, so crashes found by
+//! honggfuzz can be kept as regression tests of a normal `cargo test`.
+//!
+//! The body of the harness has to be callable from the tests, so move it from the
+//! `fuzz!` block of the fuzzing target into a function of the library:
+//!
+//! ```rust
+//! pub fn parse_harness(data: &[u8]) {
+//!     if data == b"hey" {
+//!         panic!("BOOM")
+//!     }
+//! }
+//!
+//! # fn main() {
+//! // in the fuzzing target
+//! // loop { hongg::fuzz!(|data: &[u8]| { parse_harness(data) }); }
+//!
+//! // in a regression test, generated by `cargo hongg export-tests`
+//! hongg::replay::run(b"hex", parse_harness);
+//! # }
+//! ```
+//!
+//! Whole corpus or crash directories can be replayed with [`run_dir`], or with
+//! [`replay_dir!`](crate::replay_dir) to decode the inputs with `arbitrary` like [`fuzz!`](crate::fuzz) does.
+
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+/// Run the harness `closure` on `data`, like `fuzz` does with an input generated by honggfuzz.
+pub fn run<F>(data: &[u8], closure: F)
+where
+    F: FnOnce(&[u8]),
+{
+    closure(data);
+}
+
+/// Run the harness `closure` on the content of the file at `path`.
+///
+/// If the harness panics, the path of the input is reported before the panic is resumed.
+pub fn run_file<F>(path: impl AsRef<Path>, closure: F)
+where
+    F: FnOnce(&[u8]),
+{
+    let path = path.as_ref();
+    let data = read(path);
+    if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| closure(&data))) {
+        eprintln!("input \"{}\" panicked", path.display());
+        panic::resume_unwind(payload);
+    }
+}
+
+/// Run the harness `closure` on the content of every input file directly within `dir`,
+/// see [`is_input`].
+///
+/// All files are replayed even if some of them panic, afterwards a panic reports
+/// all inputs which panicked.
+pub fn run_dir<F>(dir: impl AsRef<Path>, mut closure: F)
+where
+    F: FnMut(&[u8]),
+{
+    let dir = dir.as_ref();
+    let entries = fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("failed to read directory \"{}\": {}", dir.display(), e));
+    let mut paths = Vec::from_iter(entries.filter_map(|entry| {
+        let entry = entry.ok()?;
+        let path = entry.path();
+        (path.is_file() && is_input(&entry.file_name().to_string_lossy())).then_some(path)
+    }));
+    paths.sort();
+
+    let mut panicked = Vec::<PathBuf>::new();
+    for path in &paths {
+        let data = read(path);
+        if panic::catch_unwind(AssertUnwindSafe(|| closure(&data))).is_err() {
+            eprintln!("input \"{}\" panicked", path.display());
+            panicked.push(path.clone());
+        }
+    }
+
+    if !panicked.is_empty() {
+        panic!(
+            "{} of {} inputs in \"{}\" panicked: {}",
+            panicked.len(),
+            paths.len(),
+            dir.display(),
+            Vec::from_iter(panicked.iter().map(|path| path.display().to_string())).join(", ")
+        );
+    }
+}
+
+/// Whether the file named `name` within a corpus or crash directory is an input, rather than
+/// a hidden file, the report or the stats of honggfuzz, a saved backtrace or a dictionary.
+pub fn is_input(name: &str) -> bool {
+    !name.starts_with('.')
+        && name != "HONGGFUZZ.REPORT.TXT"
+        && !name.ends_with(".backtrace")
+        && !name.ends_with(".dict")
+        && !name.ends_with(".csv")
+}
+
+fn read(path: &Path) -> Vec<u8> {
+    fs::read(path).unwrap_or_else(|e| panic!("failed to read \"{}\": {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn crash_dir() {
+        let dir = env::temp_dir().join(format!("hongg-replay-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("logs")).unwrap();
+        fs::write(dir.join("SIGABRT.PC.0.STACK.0.fuzz"), b"crash").unwrap();
+        fs::write(dir.join("SIGABRT.PC.0.STACK.0.fuzz.backtrace"), b"#0 abort").unwrap();
+        fs::write(dir.join("HONGGFUZZ.REPORT.TXT"), b"=====").unwrap();
+        fs::write(dir.join("stats.csv"), b"1, 2").unwrap();
+        fs::write(dir.join("target.dict"), b"\"GET\"").unwrap();
+        fs::write(dir.join(".tmin"), b"").unwrap();
+        fs::write(dir.join("logs").join("honggfuzz.log"), b"log").unwrap();
+
+        let mut replayed = Vec::new();
+        run_dir(&dir, |data| replayed.push(data.to_vec()));
+        assert_eq!(replayed, vec![b"crash".to_vec()]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}